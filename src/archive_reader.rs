@@ -1,21 +1,35 @@
+use std::collections::{BTreeMap, HashMap};
 use std::error::Error;
 use std::fmt::{Display, Formatter};
 use std::fs::File;
-use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::io::{BufRead, BufReader, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
-use std::{fs, io};
+use std::time::{Duration, Instant, SystemTime};
+use std::{fs, io, thread};
 
 use binrw::BinRead;
 
-use crate::compression::extract_data;
+use crate::compression::{decompress_chunked, decompress_zlib_stream, DEFAULT_CHUNK_SIZE};
+use crate::converter::converter;
 use crate::display::{ascii_value, spaced_hex};
 use crate::formats::*;
-use crate::ArchivedFileInfo;
+use crate::name_encoding::NameEncoding;
+use crate::{ArchivedFileInfo, CompressionMethod, Warning};
 
 /// An archive type must implement ArchiveReader to be readable
 pub trait ArchiveReader<R: BufRead + Seek> {
     /// Returns file count of the archive
     fn file_count(&self) -> u64;
+    /// Returns the size of the header section, file data is stored after it
+    ///
+    /// Defaults to the lowest file data offset, or 0 if the archive has no files
+    fn header_size(&self) -> u64 {
+        self.multiple_file_info(self.file_names())
+            .iter()
+            .map(|(_, file_info)| file_info.offset)
+            .min()
+            .unwrap_or(0)
+    }
     /// Returns file names of all files in the archive
     fn file_names(&self) -> Vec<String>;
     /// Returns ArchivedFileInfo for the given file name, if any
@@ -26,17 +40,155 @@ pub trait ArchiveReader<R: BufRead + Seek> {
     ///
     /// If there are multiple files with the same name, all of them are returned
     fn multiple_file_info(&self, file_names: Vec<String>) -> Vec<(String, ArchivedFileInfo)>;
+    /// Returns the names of all folders containing files, in the order they first appear in the
+    /// file headers
+    ///
+    /// Files without a folder are in the folder with an empty name
+    fn folders(&self) -> Vec<String> {
+        let mut folders: Vec<String> = Vec::new();
+        for file_name in self.file_names() {
+            let folder = folder_name(&file_name);
+            if !folders.iter().any(|known| known == folder) {
+                folders.push(folder.to_string());
+            }
+        }
+        folders
+    }
+    /// Returns the names of all files directly inside the given folder, in file header order
+    fn files_in_folder(&self, folder: &str) -> Vec<String> {
+        self.file_names()
+            .into_iter()
+            .filter(|file_name| folder_name(file_name) == folder)
+            .collect()
+    }
     /// Returns a mutable reference to the internal reader
     fn reader(&mut self) -> &mut R;
+    /// Returns the file name, absolute offset of the CRC32 field in the file header and
+    /// ArchivedFileInfo of every file that has a CRC32 stored, in file header order
+    ///
+    /// Formats without CRC32 values return an empty Vec
+    fn hash_field_offsets(&self) -> Vec<(String, u64, ArchivedFileInfo)> {
+        Vec::new()
+    }
+    /// Returns the file name, absolute offset of the packed size field in the file header and
+    /// ArchivedFileInfo of every file, in file header order
+    ///
+    /// Formats whose file headers can not be located return an empty Vec
+    fn size_field_offsets(&self) -> Vec<(String, u64, ArchivedFileInfo)> {
+        Vec::new()
+    }
+    /// Returns the file name, absolute offset and size of the file name field of every file whose
+    /// name is stored in a fixed size field of its file header, in file header order
+    ///
+    /// Such names can be changed in place without moving any other data. Formats storing names
+    /// in any other way return an empty Vec
+    fn name_field_offsets(&self) -> Vec<(String, u64, u64)> {
+        Vec::new()
+    }
+    /// Returns the file name and flags of every file with flags set which bfstool does not know
+    /// the meaning of, in file header order
+    ///
+    /// Formats without file flags return an empty Vec
+    fn unknown_flags(&self) -> Vec<(String, u8)> {
+        Vec::new()
+    }
+    /// Returns the file name and flags of every file as stored in the file header, including flags
+    /// bfstool does not know the meaning of, in file header order
+    ///
+    /// Formats without file flags return an empty Vec
+    fn file_flags(&self) -> Vec<(String, u8)> {
+        Vec::new()
+    }
+    /// Returns warnings about problems found in the archive that do not prevent reading it
+    fn warnings(&self) -> Vec<Warning> {
+        let file_names = self.file_names();
+        let mut warnings = Vec::new();
+
+        let unnamed_files = file_names
+            .iter()
+            .filter(|file_name| file_name.is_empty())
+            .count() as u64;
+        if unnamed_files > 0 {
+            warnings.push(Warning::UnnamedFiles {
+                count: unnamed_files,
+            });
+        }
+
+        let case_collisions = case_collisions(&file_names);
+        if !case_collisions.is_empty() {
+            warnings.push(Warning::CaseCollisions {
+                names: case_collisions,
+            });
+        }
+
+        let unknown_flags = self.unknown_flags();
+        if !unknown_flags.is_empty() {
+            warnings.push(Warning::UnknownFlags {
+                files: unknown_flags,
+            });
+        }
+
+        warnings
+    }
+    /// Extracts the data of a single file described by `archived_file_info` into `writer`
+    ///
+    /// Returns the amount of bytes written
+    fn extract_file_data(
+        &mut self,
+        archived_file_info: &ArchivedFileInfo,
+        writer: &mut dyn Write,
+    ) -> io::Result<u64> {
+        self.extract_file_data_chunked(archived_file_info, writer, DEFAULT_CHUNK_SIZE)
+    }
+    /// Extracts the data of a single file like [ArchiveReader::extract_file_data], writing it in
+    /// chunks of at most `chunk_size` bytes
+    fn extract_file_data_chunked(
+        &mut self,
+        archived_file_info: &ArchivedFileInfo,
+        writer: &mut dyn Write,
+        chunk_size: usize,
+    ) -> io::Result<u64> {
+        let reader = self.reader();
+        reader.seek(SeekFrom::Start(archived_file_info.offset))?;
+        decompress_chunked(
+            reader,
+            writer,
+            archived_file_info.compressed_size,
+            archived_file_info.compression_method,
+            chunk_size,
+        )
+    }
     /// Extracts listed files from the archive to the given folder
+    ///
+    /// Files sharing the same data (same offset, size and compression method) are only
+    /// decompressed once, further copies are copied from the first extracted file
     fn extract_files<'a>(
         &mut self,
         file_names: Vec<String>,
         folder_name: &Path,
         callback: Box<dyn Fn(&str, ArchivedFileInfo) + 'a>,
+    ) -> io::Result<()> {
+        self.extract_files_with_options(
+            file_names,
+            folder_name,
+            ExtractOptions::default(),
+            callback,
+        )
+    }
+    /// Extracts listed files from the archive to the given folder using the given options
+    ///
+    /// See [ArchiveReader::extract_files] and [ExtractOptions]
+    fn extract_files_with_options<'a>(
+        &mut self,
+        file_names: Vec<String>,
+        folder_name: &Path,
+        options: ExtractOptions,
+        callback: Box<dyn Fn(&str, ArchivedFileInfo) + 'a>,
     ) -> io::Result<()> {
         let file_info = self.multiple_file_info(file_names);
-        let reader = self.reader();
+        let mut extracted_paths: HashMap<(u64, u64, CompressionMethod), PathBuf> = HashMap::new();
+        let mut output_paths = Vec::new();
+        let mut throttle = options.throttle.map(Throttle::new);
         file_info
             .into_iter()
             .try_for_each(|(file_name, archived_file_info)| {
@@ -45,54 +197,266 @@ pub trait ArchiveReader<R: BufRead + Seek> {
                 } else {
                     file_name
                 };
-                let file_path = PathBuf::from(&file_name);
-                fs::create_dir_all(folder_name.join(file_path.parent().unwrap_or(Path::new(""))))?;
-                let mut output_file = File::create(folder_name.join(file_path))?;
-
-                reader.seek(SeekFrom::Start(archived_file_info.offset))?;
-                extract_data(
-                    reader,
-                    &mut output_file,
+                let output_path = extraction_path(folder_name, &file_name);
+                fs::create_dir_all(output_path.parent().unwrap_or(folder_name))?;
+
+                let data_key = (
+                    archived_file_info.offset,
                     archived_file_info.compressed_size,
                     archived_file_info.compression_method,
-                )?;
+                );
+                let extracted_path = extracted_paths
+                    .get(&data_key)
+                    .filter(|extracted_path| **extracted_path != output_path)
+                    .cloned();
+
+                // Writing into an existing file would also change the files hard linked to it
+                if output_path.exists() {
+                    fs::remove_file(&output_path)?;
+                }
+                // Data extracted to this path before is overwritten, it can not be copied anymore
+                extracted_paths.retain(|_, extracted_path| *extracted_path != output_path);
+
+                match extracted_path {
+                    Some(extracted_path) => {
+                        // Fall back to copying, e.g. if the file system has no hard links
+                        if !options.link_duplicates
+                            || fs::hard_link(&extracted_path, &output_path).is_err()
+                        {
+                            let copied = fs::copy(&extracted_path, &output_path)?;
+                            if let Some(throttle) = throttle.as_mut() {
+                                throttle.transfer(copied);
+                            }
+                        }
+                    }
+                    None => {
+                        let mut output_file = ThrottledWriter {
+                            writer: File::create(&output_path)?,
+                            throttle: throttle.as_mut(),
+                        };
+                        let chunk_size = options.chunk_size.unwrap_or(DEFAULT_CHUNK_SIZE);
+                        if options.ignore_packed_size
+                            && archived_file_info.compression_method == CompressionMethod::Zlib
+                        {
+                            let reader = self.reader();
+                            reader.seek(SeekFrom::Start(archived_file_info.offset))?;
+                            decompress_zlib_stream(reader, &mut output_file, chunk_size)?;
+                        } else {
+                            self.extract_file_data_chunked(
+                                &archived_file_info,
+                                &mut output_file,
+                                chunk_size,
+                            )?;
+                        }
+                        drop(output_file);
+                        if options.convert {
+                            if let Some(converter) = converter(&output_path) {
+                                converter.convert(&output_path)?;
+                            }
+                        }
+                        extracted_paths.insert(data_key, output_path.clone());
+                    }
+                }
                 callback(file_name.as_ref(), archived_file_info);
+                output_paths.push(output_path);
 
                 Ok(())
-            })
+            })?;
+
+        // Attributes are set only after all files are written, as hard linked files share them
+        if let Some(modified) = options.modified {
+            output_paths.iter().try_for_each(|output_path| {
+                File::options()
+                    .write(true)
+                    .open(output_path)?
+                    .set_modified(modified)
+            })?;
+        }
+        if options.readonly {
+            output_paths.iter().try_for_each(|output_path| {
+                let mut permissions = fs::metadata(output_path)?.permissions();
+                permissions.set_readonly(true);
+                fs::set_permissions(output_path, permissions)
+            })?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Options for [ArchiveReader::extract_files_with_options]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct ExtractOptions {
+    /// Create hard links instead of copies for files sharing the same data
+    pub link_duplicates: bool,
+    /// Modification time to set on all extracted files, left as the extraction time if None
+    pub modified: Option<SystemTime>,
+    /// Mark all extracted files as read-only
+    pub readonly: bool,
+    /// Decompress zlib compressed files until their stream ends instead of reading the packed
+    /// size from the file header, recovering files whose packed size is damaged
+    pub ignore_packed_size: bool,
+    /// Size of the chunks extracted data is written in, [DEFAULT_CHUNK_SIZE] if None
+    ///
+    /// Peak memory use of the extraction does not depend on the size of the extracted files
+    pub chunk_size: Option<usize>,
+    /// Pass extracted files to the [Converter](crate::converter::Converter) registered for their
+    /// extension, see [register_converter](crate::converter::register_converter)
+    pub convert: bool,
+    /// Maximum amount of bytes written per second on average, unlimited if None
+    pub throttle: Option<u64>,
+}
+
+/// Returns the name of the folder containing the given file, or an empty name if it has none
+pub fn folder_name(file_name: &str) -> &str {
+    file_name
+        .rsplit_once(['/', '\\'])
+        .map_or("", |(folder_name, _)| folder_name)
+}
+
+/// Returns the given file name with every `\` replaced by `/`, the separator used in archives
+///
+/// Used for file names given by the user, which may use Windows-style separators
+pub fn normalize_separators(file_name: &str) -> String {
+    file_name.replace('\\', "/")
+}
+
+/// Returns the path the archived file with the given name is extracted to inside `folder`
+///
+/// Both `/` and `\` are treated as separators. Empty, `.` and `..` components are skipped, so
+/// files can not be extracted outside of `folder`.
+pub fn extraction_path(folder: &Path, file_name: &str) -> PathBuf {
+    file_name
+        .split(['/', '\\'])
+        .filter(|component| !matches!(*component, "" | "." | ".."))
+        .fold(folder.to_path_buf(), |path, component| path.join(component))
+}
+
+/// Returns groups of the given file names which only differ in case
+///
+/// Names are folded to lowercase, matching case-insensitive file systems for all ASCII and most
+/// other characters
+pub fn case_collisions(file_names: &[String]) -> Vec<Vec<String>> {
+    let mut folded_names: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for file_name in file_names {
+        let names = folded_names.entry(file_name.to_lowercase()).or_default();
+        if !names.contains(file_name) {
+            names.push(file_name.clone());
+        }
     }
+    folded_names
+        .into_values()
+        .filter(|names| names.len() > 1)
+        .collect()
+}
+
+/// Opens an archive file for reading
+///
+/// Other processes can keep reading, writing or deleting the archive while it is open, so
+/// archives of a running game can be read. If another process opened the archive without
+/// allowing that, which only happens on Windows, an error saying that the archive is in use is
+/// returned instead of the generic sharing violation.
+pub fn open_archive_file(archive: &Path) -> io::Result<File> {
+    File::open(archive).map_err(|error| {
+        if is_sharing_violation(&error) {
+            io::Error::new(
+                io::ErrorKind::ResourceBusy,
+                format!(
+                    "{} is in use by another process, close it and try again",
+                    archive.to_string_lossy()
+                ),
+            )
+        } else {
+            error
+        }
+    })
+}
+
+/// Returns true if the error is caused by another process locking the file
+fn is_sharing_violation(error: &io::Error) -> bool {
+    // ERROR_SHARING_VIOLATION and ERROR_LOCK_VIOLATION
+    cfg!(windows) && matches!(error.raw_os_error(), Some(32) | Some(33))
+}
+
+/// Options for [read_archive_with_options]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct ReadOptions {
+    /// Skip the Magic / Version / Hash size check
+    pub force: bool,
+    /// Encoding the file names in the archive are stored in
+    pub name_encoding: NameEncoding,
 }
 
 /// Read an archive file with the provided format, returning an ArchiveReader impl
 ///
 /// If `force` is true then Magic / Version / Hash size check are skipped
 ///
-/// Utility function that opens a file then calls [read_archive] on it
+/// Utility function that opens a file with [open_archive_file] then calls [read_archive] on it
 pub fn read_archive_file(
     archive: &PathBuf,
     archive_format: Format,
     force: bool,
 ) -> Result<Box<dyn ArchiveReader<BufReader<File>>>, ReadError> {
-    let file = File::open(archive)?;
+    read_archive_file_with_options(
+        archive,
+        archive_format,
+        ReadOptions {
+            force,
+            ..Default::default()
+        },
+    )
+}
+
+/// Read an archive file with the provided format and options, returning an ArchiveReader impl
+///
+/// Utility function that opens a file with [open_archive_file] then calls
+/// [read_archive_with_options] on it
+pub fn read_archive_file_with_options(
+    archive: &PathBuf,
+    archive_format: Format,
+    options: ReadOptions,
+) -> Result<Box<dyn ArchiveReader<BufReader<File>>>, ReadError> {
+    let file = open_archive_file(archive)?;
     let file_reader = BufReader::new(file);
-    read_archive(file_reader, archive_format, force)
+    read_archive_with_options(file_reader, archive_format, options)
 }
 
 /// Read an archive with the provided format, returning an ArchiveReader impl
 ///
 /// If `force` is true then Magic / Version / Hash size check are skipped
 pub fn read_archive<R: BufRead + Seek + 'static>(
-    mut archive: R,
+    archive: R,
     archive_format: Format,
     force: bool,
 ) -> Result<Box<dyn ArchiveReader<R>>, ReadError> {
+    read_archive_with_options(
+        archive,
+        archive_format,
+        ReadOptions {
+            force,
+            ..Default::default()
+        },
+    )
+}
+
+/// Read an archive with the provided format and options, returning an ArchiveReader impl
+pub fn read_archive_with_options<R: BufRead + Seek + 'static>(
+    mut archive: R,
+    archive_format: Format,
+    options: ReadOptions,
+) -> Result<Box<dyn ArchiveReader<R>>, ReadError> {
+    let ReadOptions {
+        force,
+        name_encoding,
+    } = options;
+    trace_span!("read_archive", format = %archive_format);
     match archive_format {
         Format::Bfs2004a => {
             if !force {
-                bfs2004a::check_archive(&mut archive)?;
+                check_format(&mut archive, Format::Bfs2004a, bfs2004a::check_archive)?;
             }
             archive.seek(SeekFrom::Start(0))?;
-            let raw_archive = bfs2004a::RawArchive::read(&mut archive)?;
+            let raw_archive = bfs2004a::RawArchive::read_args(&mut archive, (name_encoding,))?;
             Ok(Box::new(bfs2004a::ReadArchive {
                 reader: archive,
                 raw_archive,
@@ -100,7 +464,7 @@ pub fn read_archive<R: BufRead + Seek + 'static>(
         }
         Format::Bfs2004b => {
             if !force {
-                bfs2004b::check_archive(&mut archive)?;
+                check_format(&mut archive, Format::Bfs2004b, bfs2004b::check_archive)?;
             }
             archive.seek(SeekFrom::Start(0))?;
             let raw_archive = bfs2004b::RawArchive::read(&mut archive)?;
@@ -109,6 +473,7 @@ pub fn read_archive<R: BufRead + Seek + 'static>(
                 &raw_archive.file_name_length_table,
                 &raw_archive.serialized_huffman_dict,
                 &raw_archive.encoded_huffman_data,
+                name_encoding,
             );
             Ok(Box::new(bfs2004b::ReadArchive {
                 reader: archive,
@@ -118,7 +483,7 @@ pub fn read_archive<R: BufRead + Seek + 'static>(
         }
         Format::Bfs2007 => {
             if !force {
-                bfs2007::check_archive(&mut archive)?;
+                check_format(&mut archive, Format::Bfs2007, bfs2007::check_archive)?;
             }
             archive.seek(SeekFrom::Start(0))?;
             let raw_archive = bfs2007::RawArchive::read(&mut archive)?;
@@ -127,6 +492,7 @@ pub fn read_archive<R: BufRead + Seek + 'static>(
                 &raw_archive.file_name_length_table,
                 &raw_archive.serialized_huffman_dict,
                 &raw_archive.encoded_huffman_data,
+                name_encoding,
             );
             Ok(Box::new(bfs2007::ReadArchive {
                 reader: archive,
@@ -136,10 +502,15 @@ pub fn read_archive<R: BufRead + Seek + 'static>(
         }
         Format::Bzf2001 => {
             if !force {
-                bzf2001::check_archive(&mut archive)?;
+                check_format(&mut archive, Format::Bzf2001, bzf2001::check_archive)?;
+                if bzf2001::is_likely_encrypted(&mut archive)? {
+                    return Err(ReadError::LikelyEncrypted {
+                        format: Format::Bzf2001,
+                    });
+                }
             }
             archive.seek(SeekFrom::Start(0))?;
-            let raw_archive = bzf2001::RawArchive::read(&mut archive)?;
+            let raw_archive = bzf2001::RawArchive::read_args(&mut archive, (name_encoding,))?;
             Ok(Box::new(bzf2001::ReadArchive {
                 reader: archive,
                 raw_archive,
@@ -147,17 +518,190 @@ pub fn read_archive<R: BufRead + Seek + 'static>(
         }
         Format::Bzf2002 => {
             if !force {
-                bzf2002::check_archive(&mut archive)?;
+                check_format(&mut archive, Format::Bzf2002, bzf2002::check_archive)?;
             }
             archive.seek(SeekFrom::Start(0))?;
-            let raw_archive = bzf2002::RawArchive::read(&mut archive)?;
+            let raw_archive = bzf2002::RawArchive::read_args(&mut archive, (name_encoding,))?;
             Ok(Box::new(bzf2002::ReadArchive {
                 reader: archive,
                 raw_archive,
             }))
         }
-        _ => todo!(),
+        _ => Err(ReadError::UnsupportedFormat {
+            format: archive_format,
+        }),
+    }
+}
+
+/// Detect the format of an archive by running the checks of all readable formats
+///
+/// Returns None if the archive does not match any of them
+pub fn probe<R: BufRead + Seek>(archive: &mut R) -> Option<Format> {
+    let checks: [(Format, fn(&mut R) -> Result<(), ReadError>); 5] = [
+        (Format::Bzf2001, bzf2001::check_archive),
+        (Format::Bzf2002, bzf2002::check_archive),
+        (Format::Bfs2004a, bfs2004a::check_archive),
+        (Format::Bfs2004b, bfs2004b::check_archive),
+        (Format::Bfs2007, bfs2007::check_archive),
+    ];
+    checks
+        .into_iter()
+        .find_map(|(format, check)| check(archive).is_ok().then_some(format))
+}
+
+/// Checks whether an archive of the given format, as detected by [probe], appears to be encrypted
+///
+/// Encrypted archives pass the format checks, as only their file headers are encrypted. Always
+/// returns false for formats without encryption.
+pub fn probe_encrypted<R: BufRead + Seek>(archive: &mut R, format: Format) -> bool {
+    match format {
+        Format::Bzf2001 => bzf2001::is_likely_encrypted(archive).unwrap_or(false),
+        _ => false,
+    }
+}
+
+/// Checks whether an archive passes the checks of the given format, to be used when reading it
+/// with `force`
+///
+/// Returns a [Warning::FormatChecksFailed] if it does not
+pub fn check_forced_format<R: BufRead + Seek>(archive: &mut R, format: Format) -> Option<Warning> {
+    let detected = probe(archive);
+    if detected == Some(format) {
+        None
+    } else {
+        Some(Warning::FormatChecksFailed { format, detected })
+    }
+}
+
+/// Finds archived files that are archives themselves, returning their names, ArchivedFileInfo
+/// and detected formats
+///
+/// Only the start of every file is decompressed to check its magic. Files with a magic belonging to
+/// a readable format are then decompressed into memory to run the checks of the format.
+pub fn find_nested_archives<R: BufRead + Seek>(
+    archive: &mut dyn ArchiveReader<R>,
+) -> io::Result<Vec<(String, ArchivedFileInfo, Format)>> {
+    let magics = [bzf2001::MAGIC, bzf2002::MAGIC, bfs2004a::MAGIC].map(u32::to_le_bytes);
+
+    let mut nested_archives = Vec::new();
+    for (file_name, archived_file_info) in archive.multiple_file_info(archive.file_names()) {
+        if archived_file_info.size < 4 {
+            continue;
+        }
+        let mut magic_writer = MagicWriter::default();
+        match archive.extract_file_data(&archived_file_info, &mut magic_writer) {
+            Ok(_) => {}
+            // The writer stops the extraction once it has the magic
+            Err(error) if error.kind() == io::ErrorKind::WriteZero && magic_writer.is_full() => {}
+            Err(error) => return Err(error),
+        }
+        if !magics.contains(&magic_writer.magic) {
+            continue;
+        }
+        let mut data = Vec::new();
+        archive.extract_file_data(&archived_file_info, &mut data)?;
+        if let Some(format) = probe(&mut io::Cursor::new(data)) {
+            nested_archives.push((file_name, archived_file_info, format));
+        }
+    }
+
+    Ok(nested_archives)
+}
+
+/// Limits the average rate of data transfers
+struct Throttle {
+    bytes_per_second: u64,
+    start: Instant,
+    transferred: u64,
+}
+
+impl Throttle {
+    fn new(bytes_per_second: u64) -> Self {
+        Throttle {
+            bytes_per_second: bytes_per_second.max(1),
+            start: Instant::now(),
+            transferred: 0,
+        }
+    }
+
+    /// Records a transfer of `bytes`, sleeping until the average rate since the start is within
+    /// the limit
+    fn transfer(&mut self, bytes: u64) {
+        self.transferred += bytes;
+        let expected =
+            Duration::from_secs_f64(self.transferred as f64 / self.bytes_per_second as f64);
+        let elapsed = self.start.elapsed();
+        if expected > elapsed {
+            thread::sleep(expected - elapsed);
+        }
+    }
+}
+
+/// Writer passing everything written to it through a [Throttle], if any
+struct ThrottledWriter<'a, W: Write> {
+    writer: W,
+    throttle: Option<&'a mut Throttle>,
+}
+
+impl<W: Write> Write for ThrottledWriter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.writer.write(buf)?;
+        if let Some(throttle) = self.throttle.as_mut() {
+            throttle.transfer(written as u64);
+        }
+        Ok(written)
     }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// Writer keeping the first 4 bytes written to it
+///
+/// Once it has them, writes fail with [io::ErrorKind::WriteZero], so the rest of the file is not
+/// decompressed
+#[derive(Default)]
+struct MagicWriter {
+    magic: [u8; 4],
+    written: usize,
+}
+
+impl MagicWriter {
+    fn is_full(&self) -> bool {
+        self.written == self.magic.len()
+    }
+}
+
+impl Write for MagicWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let copied = (self.magic.len() - self.written).min(buf.len());
+        self.magic[self.written..self.written + copied].copy_from_slice(&buf[..copied]);
+        self.written += copied;
+        Ok(copied)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Runs the check of the given format
+///
+/// If the check fails and the archive passes the check of another format, a
+/// [ReadError::FormatMismatch] naming the detected format is returned instead
+fn check_format<R: BufRead + Seek>(
+    archive: &mut R,
+    format: Format,
+    check: fn(&mut R) -> Result<(), ReadError>,
+) -> Result<(), ReadError> {
+    check(archive).map_err(|error| match probe(archive) {
+        Some(detected) if detected != format => ReadError::FormatMismatch {
+            expected: format,
+            detected,
+        },
+        _ => error,
+    })
 }
 
 /// Errors that can occur while reading the archive
@@ -185,6 +729,29 @@ pub enum ReadError {
         /// Actual hash size
         got: u32,
     },
+    /// Archive is not of the expected format, but matches another one
+    FormatMismatch {
+        /// Expected format
+        expected: Format,
+        /// Detected format
+        detected: Format,
+    },
+    /// Archive appears to be encrypted and has to be decrypted before reading
+    LikelyEncrypted {
+        /// Format of the encrypted archive
+        format: Format,
+    },
+    /// Archive format can not be read by bfstool itself, only by a
+    /// [FormatProvider](crate::format_provider::FormatProvider)
+    UnsupportedFormat {
+        /// Format of the archive
+        format: Format,
+    },
+    /// No [FormatProvider](crate::format_provider::FormatProvider) is registered under the name
+    NoFormatProvider {
+        /// Name of the format
+        name: String,
+    },
     /// An IO error occurred
     IoError(io::Error),
     /// Error while parsing with binrw
@@ -231,6 +798,30 @@ impl Display for ReadError {
                     expected, got,
                 )
             }
+            ReadError::FormatMismatch { expected, detected } => {
+                write!(
+                    f,
+                    "Archive format does not match - expected: {}, detected: {}",
+                    expected, detected,
+                )
+            }
+            ReadError::LikelyEncrypted { format } => {
+                write!(
+                    f,
+                    "Archive appears to be encrypted ({}), it has to be decrypted first",
+                    format
+                )
+            }
+            ReadError::UnsupportedFormat { format } => {
+                write!(
+                    f,
+                    "{} archives can not be read, a format provider has to be registered for them",
+                    format
+                )
+            }
+            ReadError::NoFormatProvider { name } => {
+                write!(f, "No format provider is registered for {}", name)
+            }
             ReadError::IoError(error) => {
                 write!(f, "An IO error occurred: {}", error)
             }
@@ -1,7 +1,9 @@
+use std::cmp::Ordering;
+
 use crate::CompressionMethod;
 
 /// Provides information about an archived file, without the name
-#[derive(Debug, Default, Eq, PartialEq)]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
 pub struct ArchivedFileInfo {
     /// Offset of this file in the archive
     pub offset: u64,
@@ -13,6 +15,156 @@ pub struct ArchivedFileInfo {
     pub compressed_size: u64,
     /// Number of copies of this file
     pub copies: u64,
+    /// Absolute offsets of all additional copies of this file
+    pub copies_offsets: Vec<u64>,
     /// File hash
     pub hash: Option<u32>,
 }
+
+impl ArchivedFileInfo {
+    /// Returns a builder for an ArchivedFileInfo, starting from the default values
+    pub fn builder() -> ArchivedFileInfoBuilder {
+        ArchivedFileInfoBuilder::default()
+    }
+
+    /// Compares the position of the stored data of two files in the archive, by offset first and
+    /// compressed size second
+    pub fn cmp_position(&self, other: &Self) -> Ordering {
+        (self.offset, self.compressed_size).cmp(&(other.offset, other.compressed_size))
+    }
+
+    /// Returns true if both files are stored as the same data in the archive, i.e. have the same
+    /// offset, compressed size and compression method
+    pub fn same_data(&self, other: &Self) -> bool {
+        self.offset == other.offset
+            && self.compressed_size == other.compressed_size
+            && self.compression_method == other.compression_method
+    }
+
+    /// Returns the names of all fields differing between the two ArchivedFileInfo
+    pub fn differences(&self, other: &Self) -> Vec<&'static str> {
+        [
+            ("offset", self.offset != other.offset),
+            (
+                "compression_method",
+                self.compression_method != other.compression_method,
+            ),
+            ("size", self.size != other.size),
+            (
+                "compressed_size",
+                self.compressed_size != other.compressed_size,
+            ),
+            ("copies", self.copies != other.copies),
+            (
+                "copies_offsets",
+                self.copies_offsets != other.copies_offsets,
+            ),
+            ("hash", self.hash != other.hash),
+        ]
+        .into_iter()
+        .filter_map(|(field, differs)| differs.then_some(field))
+        .collect()
+    }
+}
+
+/// Builder for [ArchivedFileInfo], created with [ArchivedFileInfo::builder]
+#[derive(Clone, Debug, Default)]
+pub struct ArchivedFileInfoBuilder {
+    file_info: ArchivedFileInfo,
+}
+
+impl ArchivedFileInfoBuilder {
+    /// Sets the offset of the file in the archive
+    pub fn offset(mut self, offset: u64) -> Self {
+        self.file_info.offset = offset;
+        self
+    }
+
+    /// Sets the compression method used by the file
+    pub fn compression_method(mut self, compression_method: CompressionMethod) -> Self {
+        self.file_info.compression_method = compression_method;
+        self
+    }
+
+    /// Sets the uncompressed size of the file
+    pub fn size(mut self, size: u64) -> Self {
+        self.file_info.size = size;
+        self
+    }
+
+    /// Sets the compressed size of the file
+    pub fn compressed_size(mut self, compressed_size: u64) -> Self {
+        self.file_info.compressed_size = compressed_size;
+        self
+    }
+
+    /// Sets the number of copies of the file
+    pub fn copies(mut self, copies: u64) -> Self {
+        self.file_info.copies = copies;
+        self
+    }
+
+    /// Sets the offsets of the additional copies of the file, without changing the number of copies
+    pub fn copies_offsets(mut self, copies_offsets: Vec<u64>) -> Self {
+        self.file_info.copies_offsets = copies_offsets;
+        self
+    }
+
+    /// Sets the file hash
+    pub fn hash(mut self, hash: u32) -> Self {
+        self.file_info.hash = Some(hash);
+        self
+    }
+
+    /// Returns the built ArchivedFileInfo
+    pub fn build(self) -> ArchivedFileInfo {
+        self.file_info
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn builder_test() {
+        let file_info = ArchivedFileInfo::builder()
+            .offset(0xFDC)
+            .compression_method(CompressionMethod::Zlib)
+            .size(0xF5F)
+            .compressed_size(0x1D7)
+            .hash(0x6E3C7E35)
+            .build();
+        assert_eq!(
+            file_info,
+            ArchivedFileInfo {
+                offset: 0xFDC,
+                compression_method: CompressionMethod::Zlib,
+                size: 0xF5F,
+                compressed_size: 0x1D7,
+                copies: 0,
+                copies_offsets: vec![],
+                hash: Some(0x6E3C7E35),
+            }
+        );
+
+        let copy = ArchivedFileInfo {
+            copies: 1,
+            copies_offsets: vec![0x11B3],
+            hash: None,
+            ..file_info.clone()
+        };
+        assert!(file_info.same_data(&copy));
+        assert_eq!(file_info.cmp_position(&copy), Ordering::Equal);
+        assert_eq!(
+            file_info.differences(&copy),
+            vec!["copies", "copies_offsets", "hash"]
+        );
+
+        let next = ArchivedFileInfo::builder().offset(0x11B3).build();
+        assert!(!file_info.same_data(&next));
+        assert_eq!(file_info.cmp_position(&next), Ordering::Less);
+    }
+}
@@ -0,0 +1,231 @@
+use std::error::Error;
+use std::io;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use clap::Parser;
+use tabled::settings::object::Segment;
+use tabled::settings::{Alignment, Modify, Style};
+use tabled::{Table, Tabled};
+
+use bfstool::compression::compress;
+use bfstool::{read_archive_file_with_options, CompressionMethod};
+
+use crate::config;
+use crate::display::display_size;
+use crate::warning::warn_archive;
+
+use super::Format;
+
+/// Levels to benchmark for zlib compression
+const ZLIB_LEVELS: [i32; 3] = [1, 6, 9];
+
+/// Levels to benchmark for Zstandard compression
+const ZSTD_LEVELS: [i32; 4] = [1, 3, 9, 19];
+
+#[derive(Parser)]
+pub struct Arguments {
+    /// BFS archive file name
+    archive: PathBuf,
+    /// Ignore invalid magic/version/hash size
+    #[clap(long)]
+    force: bool,
+    /// BFS archive format, taken from the config file if not provided
+    #[clap(short, long)]
+    format: Option<Format>,
+    /// Amount of extracted data in MiB to use for the compression benchmark
+    #[clap(long, default_value_t = 16)]
+    sample_size: u64,
+}
+
+#[derive(Tabled)]
+struct BenchResult {
+    #[tabled(rename = "Operation")]
+    operation: &'static str,
+
+    #[tabled(rename = "Method")]
+    method: String,
+
+    #[tabled(rename = "Level")]
+    level: String,
+
+    #[tabled(rename = "Input", display_with = "display_size")]
+    input: u64,
+
+    #[tabled(rename = "Output", display_with = "display_size")]
+    output: u64,
+
+    #[tabled(rename = "Time", display_with = "display_duration")]
+    time: Duration,
+
+    #[tabled(rename = "Throughput")]
+    throughput: String,
+}
+
+fn display_duration(duration: &Duration) -> String {
+    format!("{:.3} s", duration.as_secs_f64())
+}
+
+fn display_throughput(size: u64, duration: Duration) -> String {
+    if duration.is_zero() {
+        "-".to_string()
+    } else {
+        format!(
+            "{}/s",
+            display_size(&((size as f64 / duration.as_secs_f64()) as u64))
+        )
+    }
+}
+
+pub fn run(arguments: Arguments, mut writer: impl Write) -> Result<(), Box<dyn Error>> {
+    let format = config::format(arguments.format, &arguments.archive)?;
+
+    let mut results = Vec::new();
+
+    let start = Instant::now();
+    let mut archive = read_archive_file_with_options(
+        &arguments.archive,
+        format,
+        config::read_options(arguments.force),
+    )?;
+    warn_archive(&arguments.archive, archive.as_ref());
+    results.push(BenchResult {
+        operation: "open",
+        method: "-".to_string(),
+        level: "-".to_string(),
+        input: 0,
+        output: 0,
+        time: start.elapsed(),
+        throughput: "-".to_string(),
+    });
+
+    let sample_size = arguments.sample_size * 1024 * 1024;
+    let mut samples = Vec::new();
+    let mut total_sample_size = 0;
+
+    let file_info = archive.multiple_file_info(archive.file_names());
+    let mut input = 0;
+    let mut output = 0;
+    let start = Instant::now();
+    for (_, archived_file_info) in file_info {
+        input += archived_file_info.compressed_size;
+        if total_sample_size < sample_size {
+            let mut sample = Vec::new();
+            output += archive.extract_file_data(&archived_file_info, &mut sample)?;
+            total_sample_size += sample.len() as u64;
+            samples.push(sample);
+        } else {
+            output += archive.extract_file_data(&archived_file_info, &mut io::sink())?;
+        }
+    }
+    let time = start.elapsed();
+    results.push(BenchResult {
+        operation: "extract",
+        method: "-".to_string(),
+        level: "-".to_string(),
+        input,
+        output,
+        time,
+        throughput: display_throughput(output, time),
+    });
+
+    for level in ZLIB_LEVELS {
+        let start = Instant::now();
+        let output = samples.iter().try_fold(0, |acc, sample| {
+            io::Result::Ok(
+                acc + compress(
+                    &mut sample.as_slice(),
+                    &mut io::sink(),
+                    CompressionMethod::Zlib,
+                    Some(level),
+                )?,
+            )
+        })?;
+        let time = start.elapsed();
+        results.push(BenchResult {
+            operation: "compress",
+            method: CompressionMethod::Zlib.to_string(),
+            level: level.to_string(),
+            input: total_sample_size,
+            output,
+            time,
+            throughput: display_throughput(total_sample_size, time),
+        });
+    }
+
+    for level in ZSTD_LEVELS {
+        let start = Instant::now();
+        let output = samples.iter().try_fold(0, |acc, sample| {
+            io::Result::Ok(
+                acc + compress(
+                    &mut sample.as_slice(),
+                    &mut io::sink(),
+                    CompressionMethod::Zstd,
+                    Some(level),
+                )?,
+            )
+        })?;
+        let time = start.elapsed();
+        results.push(BenchResult {
+            operation: "compress",
+            method: CompressionMethod::Zstd.to_string(),
+            level: level.to_string(),
+            input: total_sample_size,
+            output,
+            time,
+            throughput: display_throughput(total_sample_size, time),
+        });
+    }
+
+    writeln!(
+        writer,
+        "Benchmarking archive: {}",
+        arguments.archive.to_string_lossy()
+    )?;
+    writeln!(
+        writer,
+        "Compression sample: {}",
+        display_size(&total_sample_size)
+    )?;
+    writeln!(
+        writer,
+        "{}",
+        Table::new(results)
+            .with(Style::markdown())
+            .with(Modify::new(Segment::all()).with(Alignment::right()))
+    )?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn bench_test() -> Result<(), Box<dyn Error>> {
+        let mut output = Vec::new();
+        run(
+            Arguments {
+                archive: PathBuf::from("test_data/bfs2004a/europe.bin"),
+                force: false,
+                format: Some(Format::Bfs2004a),
+                sample_size: 1,
+            },
+            &mut output,
+        )?;
+        let output = String::from_utf8(output)?;
+
+        assert!(output.starts_with("Benchmarking archive: test_data/bfs2004a/europe.bin\n"));
+        assert!(output.contains("Compression sample: "));
+        for row in [" open |", " extract |", " compress |"] {
+            assert!(output.contains(row), "missing {row:?} row");
+        }
+        assert_eq!(output.matches(" compress |").count(), 7);
+
+        Ok(())
+    }
+}
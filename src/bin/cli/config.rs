@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use clap::ValueEnum;
+use serde::Deserialize;
+
+use bfstool::name_encoding::{Encoding, NameEncoding};
+use bfstool::ReadOptions;
+
+use crate::exit_code::NotFound;
+use crate::messages::message;
+use crate::prompt;
+use crate::safe_write;
+
+use super::Format;
+
+static PRESET: OnceLock<Preset> = OnceLock::new();
+
+static NAME_ENCODING: OnceLock<NameEncoding> = OnceLock::new();
+
+/// Contents of the config.toml file
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Values used when no preset is selected, or the selected preset does not set them
+    #[serde(default)]
+    pub defaults: Preset,
+    /// Named presets, usually one per game, selected with `--preset`
+    #[serde(default)]
+    pub presets: HashMap<String, Preset>,
+}
+
+/// Default values for the command-line arguments
+#[derive(Deserialize, Default, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Preset {
+    /// Archive format
+    pub format: Option<Format>,
+    /// Output directory
+    pub output: Option<PathBuf>,
+    /// Encoding of file names in archives
+    pub encoding: Option<String>,
+    /// Directory temporary copies of modified archives are stored in
+    pub temp_dir: Option<PathBuf>,
+}
+
+/// Returns the location of the config file
+///
+/// `%APPDATA%\bfstool\config.toml` on Windows, `$XDG_CONFIG_HOME/bfstool/config.toml` or
+/// `~/.config/bfstool/config.toml` elsewhere
+pub fn config_path() -> Option<PathBuf> {
+    let config_dir = if cfg!(windows) {
+        env::var_os("APPDATA").map(PathBuf::from)
+    } else {
+        env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+    };
+    config_dir.map(|config_dir| config_dir.join("bfstool").join("config.toml"))
+}
+
+/// Loads the config file, if present, selects the given preset and sets the file name encoding
+/// and the temporary directory to the given ones, or the ones from the preset if not given
+///
+/// Encodings are given as WHATWG labels, for example `windows-1251` or `shift_jis`.
+///
+/// Must be called once before any command runs
+pub fn load(
+    preset_name: Option<&str>,
+    encoding: Option<String>,
+    temp_dir: Option<PathBuf>,
+) -> Result<(), Box<dyn Error>> {
+    let config = match config_path() {
+        Some(path) if path.exists() => toml::from_str::<Config>(&fs::read_to_string(path)?)?,
+        _ => Config::default(),
+    };
+
+    let mut preset = config.defaults;
+    if let Some(preset_name) = preset_name {
+        let selected = config
+            .presets
+            .get(preset_name)
+            .ok_or_else(|| NotFound(format!("Preset {}", preset_name)))?;
+        preset.format = selected.format.clone().or(preset.format);
+        preset.output = selected.output.clone().or(preset.output);
+        preset.encoding = selected.encoding.clone().or(preset.encoding);
+        preset.temp_dir = selected.temp_dir.clone().or(preset.temp_dir);
+    }
+    if let Some(encoding) = encoding.or(preset.encoding.clone()) {
+        let encoding = Encoding::for_label(encoding.as_bytes())
+            .ok_or_else(|| format!("Unknown file name encoding {}", encoding))?;
+        NAME_ENCODING.get_or_init(|| NameEncoding(encoding));
+    }
+    if let Some(temp_dir) = temp_dir.or(preset.temp_dir.clone()) {
+        safe_write::set_temp_dir(temp_dir);
+    }
+    PRESET.get_or_init(|| preset);
+
+    Ok(())
+}
+
+/// Returns the file name encoding selected with `--encoding` or in the config file, UTF-8 if
+/// none was selected
+pub fn name_encoding() -> NameEncoding {
+    NAME_ENCODING.get().copied().unwrap_or_default()
+}
+
+/// Returns the options archives are read with, using the selected file name encoding
+pub fn read_options(force: bool) -> ReadOptions {
+    ReadOptions {
+        force,
+        name_encoding: name_encoding(),
+    }
+}
+
+/// Returns the given format, or the one from the config file if not given
+///
+/// If neither is present and the terminal is interactive, the user is asked to pick one, with the
+/// format detected for the archive suggested
+pub fn format(format: Option<Format>, archive: &Path) -> Result<bfstool::Format, Box<dyn Error>> {
+    if let Some(format) = format.or_else(|| PRESET.get().and_then(|preset| preset.format.clone())) {
+        return Ok(format.into());
+    }
+    if !prompt::is_interactive() {
+        return Err(message("error.no_format", &[]).into());
+    }
+
+    let formats = Format::value_variants()
+        .iter()
+        .map(|format| bfstool::Format::from(format.clone()))
+        .collect::<Vec<bfstool::Format>>();
+    let detected = File::open(archive)
+        .ok()
+        .and_then(|file| bfstool::probe(&mut BufReader::new(file)));
+    let picked = prompt::pick(
+        &message("prompt.format", &[&archive.to_string_lossy()]),
+        &formats
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<String>>(),
+        formats.iter().position(|format| Some(*format) == detected),
+    )?;
+    Ok(formats[picked])
+}
+
+/// Returns the given output directory, or the one from the config file if not given
+///
+/// If neither is present and the terminal is interactive, the user is asked for one, with a
+/// directory named after the archive suggested
+pub fn output(output: Option<PathBuf>, archive: &Path) -> Result<PathBuf, Box<dyn Error>> {
+    if let Some(output) = output.or_else(|| PRESET.get().and_then(|preset| preset.output.clone())) {
+        return Ok(output);
+    }
+    if !prompt::is_interactive() {
+        return Err(message("error.no_output", &[]).into());
+    }
+
+    let suggestion = archive.with_extension("");
+    let answer = prompt::ask(&message("prompt.output", &[&suggestion.to_string_lossy()]))?;
+    Ok(if answer.is_empty() {
+        suggestion
+    } else {
+        PathBuf::from(answer)
+    })
+}
@@ -1,12 +1,13 @@
 use std::error::Error;
 use std::fs::File;
-use std::io::Read;
+use std::io::{BufReader, BufWriter, Read, Write};
 use std::path::PathBuf;
 
 use clap::Parser;
 
 use bfstool::keys::Keys;
 
+use crate::progress::byte_bar;
 use crate::CryptFormat;
 
 #[derive(Parser)]
@@ -29,11 +30,19 @@ pub fn run(arguments: Arguments) -> Result<(), Box<dyn Error>> {
     file.read_to_string(&mut contents)?;
     let keys = toml::from_str::<Keys>(&contents)?;
     match arguments.format {
-        CryptFormat::Bzf2001 => bfstool::crypt::bzf2001::decrypt_file(
-            arguments.input,
-            arguments.output,
-            keys.bzf2001.expect("Missing decryption key").key,
-        )?,
+        CryptFormat::Bzf2001 => {
+            let input = File::open(arguments.input)?;
+            let bar = byte_bar(input.metadata()?.len());
+            let input = bar.wrap_read(BufReader::new(input));
+            let mut output = BufWriter::new(File::create(arguments.output)?);
+            bfstool::crypt::bzf2001::decrypt(
+                input,
+                &mut output,
+                keys.bzf2001.expect("Missing decryption key").key,
+            )?;
+            output.flush()?;
+            bar.finish_and_clear();
+        }
     }
     Ok(())
 }
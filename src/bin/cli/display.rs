@@ -14,3 +14,7 @@ pub fn display_size(size: &u64) -> String {
         }
     }
 }
+
+pub fn display_crc(crc: &u32) -> String {
+    format!("{:08X}", crc)
+}
@@ -0,0 +1,194 @@
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use clap::Parser;
+
+use bfstool::archive_reader::open_archive_file;
+use bfstool::formats::partial::{probe_partial, read_partial};
+
+use crate::display::{display_offset, display_size};
+use crate::research::PartialFormat;
+
+/// Amount of bytes shown on every line of the dump
+const LINE_SIZE: usize = 0x10;
+
+#[derive(Parser)]
+pub struct Arguments {
+    /// Archive file name
+    archive: PathBuf,
+    /// Output file name, the dump is written to the standard output if not provided
+    output: Option<PathBuf>,
+    /// Archive format, detected from the magic and version if not provided
+    #[clap(short, long)]
+    format: Option<PartialFormat>,
+}
+
+pub fn run(arguments: Arguments, mut writer: impl Write) -> Result<(), Box<dyn Error>> {
+    let mut reader = BufReader::new(open_archive_file(&arguments.archive)?);
+
+    let format = match arguments.format {
+        Some(format) => format.into(),
+        None => probe_partial(&mut reader).ok_or("Unable to detect the archive format")?,
+    };
+
+    match &arguments.output {
+        Some(output) => {
+            let mut output_writer = BufWriter::new(File::create(output)?);
+            write_dump(&mut output_writer, &mut reader, &arguments.archive, format)?;
+            output_writer.flush()?;
+            writeln!(
+                writer,
+                "Unknown regions written to: {}",
+                output.to_string_lossy()
+            )?;
+        }
+        None => write_dump(&mut writer, &mut reader, &arguments.archive, format)?,
+    }
+
+    Ok(())
+}
+
+/// Writes the known header fields as comments, followed by the hex dump of every unknown region
+fn write_dump(
+    writer: &mut impl Write,
+    reader: &mut (impl Read + Seek),
+    archive_path: &Path,
+    format: bfstool::Format,
+) -> Result<(), Box<dyn Error>> {
+    let archive = read_partial(reader, format)?;
+
+    writeln!(writer, "# Archive: {}", archive_path.to_string_lossy())?;
+    writeln!(writer, "# Format: {}", format)?;
+    for field in &archive.fields {
+        writeln!(
+            writer,
+            "# {} {}: {:08X} ({})",
+            display_offset(&field.offset),
+            field.name,
+            field.value,
+            if field.confirmed {
+                "confirmed"
+            } else {
+                "assumed"
+            }
+        )?;
+    }
+
+    for region in &archive.unknown {
+        writeln!(writer)?;
+        writeln!(
+            writer,
+            "# Unknown region {}..{} ({})",
+            display_offset(&region.start),
+            display_offset(&region.end),
+            display_size(&(region.end - region.start))
+        )?;
+        reader.seek(SeekFrom::Start(region.start))?;
+        let mut data = Vec::new();
+        reader
+            .by_ref()
+            .take(region.end - region.start)
+            .read_to_end(&mut data)?;
+        for (index, line) in data.chunks(LINE_SIZE).enumerate() {
+            write_line(writer, region.start + (index * LINE_SIZE) as u64, line)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes a line of the dump: the offset, the bytes in hex, the bytes as ASCII and the bytes read
+/// as little endian u32 values, which are often offsets, sizes or counts
+fn write_line(writer: &mut impl Write, offset: u64, line: &[u8]) -> std::io::Result<()> {
+    let hex = line
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect::<Vec<String>>()
+        .join(" ");
+    let ascii = line
+        .iter()
+        .map(|byte| {
+            if byte.is_ascii_graphic() || *byte == b' ' {
+                *byte as char
+            } else {
+                '.'
+            }
+        })
+        .collect::<String>();
+    let words = line
+        .chunks_exact(4)
+        .map(|word| {
+            format!(
+                "{:08X}",
+                u32::from_le_bytes([word[0], word[1], word[2], word[3]])
+            )
+        })
+        .collect::<Vec<String>>()
+        .join(" ");
+    writeln!(
+        writer,
+        "{}  {:<width$}  |{:<ascii_width$}|  u32: {}",
+        display_offset(&offset),
+        hex,
+        ascii,
+        words,
+        width = LINE_SIZE * 3 - 1,
+        ascii_width = LINE_SIZE
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use pretty_assertions::assert_eq;
+
+    use bfstool::formats::partial::BFS2011_VERSION;
+
+    use crate::test_archive::TestDir;
+
+    use super::*;
+
+    #[test]
+    fn dump_unknown_test() -> Result<(), Box<dyn Error>> {
+        // Made up Bfs2011 archive with a 0x18 byte header
+        let mut archive = Vec::new();
+        archive.extend_from_slice(b"bfs1");
+        archive.extend_from_slice(&BFS2011_VERSION.to_le_bytes());
+        archive.extend_from_slice(&0x18u32.to_le_bytes());
+        archive.extend_from_slice(&1u32.to_le_bytes());
+        archive.extend_from_slice(b"data");
+        archive.extend_from_slice(&0x18u32.to_le_bytes());
+        archive.resize(0x20, 0);
+
+        let temp_dir = TestDir::new("dump_unknown_test")?;
+        let archive_path = temp_dir.join("archive.bin");
+        fs::write(&archive_path, archive)?;
+
+        let mut result = Vec::new();
+        let arguments = Arguments {
+            archive: archive_path,
+            output: None,
+            format: None,
+        };
+        run(arguments, &mut result)?;
+
+        let result = String::from_utf8_lossy(&result);
+        let mut lines = result.lines().skip_while(|line| !line.contains("Unknown"));
+        assert_eq!(
+            lines.next(),
+            Some("# Unknown region 00000010..00000018 (8 B)")
+        );
+        assert_eq!(
+            lines.next(),
+            Some(
+                "00000010  64 61 74 61 18 00 00 00                          |data....        |  u32: 61746164 00000018"
+            )
+        );
+        assert_eq!(lines.next(), None);
+
+        Ok(())
+    }
+}
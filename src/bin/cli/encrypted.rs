@@ -0,0 +1,138 @@
+use std::error::Error;
+use std::fs;
+use std::io::{BufReader, BufWriter, Cursor, Seek};
+use std::path::Path;
+
+use bfstool::archive_reader::{open_archive_file, ArchiveReader};
+use bfstool::format_provider::read_archive_with_provider;
+use bfstool::keys::Keys;
+use bfstool::Format;
+
+use crate::config;
+use crate::warning::{check_forced_format, warn_archive};
+
+use super::FormatName;
+
+/// Reader of an archive, either read from its file or decrypted into memory
+pub use bfstool::format_provider::ProviderSource as ArchiveSource;
+
+/// Reads an archive file like [bfstool::read_archive_file]
+///
+/// If a Keys.toml file name is provided, the archive is decrypted into memory first, so encrypted
+/// archives can be read without decrypting them to a file with the decrypt command
+pub fn read_archive_file(
+    archive: &Path,
+    format: Format,
+    force: bool,
+    keys: Option<&Path>,
+) -> Result<Box<dyn ArchiveReader<Box<dyn ArchiveSource>>>, Box<dyn Error>> {
+    let source: Box<dyn ArchiveSource> = match keys {
+        Some(keys) => Box::new(decrypt_archive(archive, format, keys)?),
+        None => Box::new(BufReader::new(open_archive_file(archive)?)),
+    };
+    Ok(bfstool::read_archive_with_options(
+        source,
+        format,
+        config::read_options(force),
+    )?)
+}
+
+/// Reads an archive file like [read_archive_file], with the format given with `--format`, or the
+/// one from the config file if not given
+///
+/// The format can also be the name of a registered format provider, which is then used to read
+/// the archive. Returns the name of the format along with the archive.
+pub fn read_archive_file_with_format_name(
+    archive_path: &Path,
+    format: Option<FormatName>,
+    force: bool,
+    keys: Option<&Path>,
+) -> Result<(String, Box<dyn ArchiveReader<Box<dyn ArchiveSource>>>), Box<dyn Error>> {
+    let format = match format {
+        Some(FormatName::Provider(name)) => {
+            if keys.is_some() {
+                return Err("Archives read with a format provider can not be decrypted".into());
+            }
+            let source: Box<dyn ArchiveSource> =
+                Box::new(BufReader::new(open_archive_file(archive_path)?));
+            let archive = read_archive_with_provider(source, &name, force)?;
+            warn_archive(archive_path, archive.as_ref());
+            return Ok((name, archive));
+        }
+        Some(FormatName::Builtin(format)) => Some(format),
+        None => None,
+    };
+    let format = config::format(format, archive_path)?;
+
+    if force {
+        check_forced_format(archive_path, format)?;
+    }
+
+    let archive = read_archive_file(archive_path, format, force, keys)?;
+    warn_archive(archive_path, archive.as_ref());
+    Ok((format.to_string(), archive))
+}
+
+/// Decrypts an archive of the given format into memory using the keys from the given Keys.toml
+fn decrypt_archive(
+    archive: &Path,
+    format: Format,
+    keys: &Path,
+) -> Result<Cursor<Vec<u8>>, Box<dyn Error>> {
+    let keys = toml::from_str::<Keys>(&fs::read_to_string(keys)?)?;
+    let input = BufReader::new(open_archive_file(archive)?);
+    let mut output = BufWriter::new(Cursor::new(Vec::new()));
+    match format {
+        Format::Bzf2001 => {
+            let key = keys.bzf2001.ok_or("Missing decryption key")?.key;
+            bfstool::crypt::bzf2001::decrypt(input, &mut output, key)?;
+        }
+        _ => return Err(format!("{} archives are not encrypted", format).into()),
+    }
+    let mut decrypted = output.into_inner()?;
+    decrypted.rewind()?;
+    Ok(decrypted)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use bfstool::keys::Bzf2001Keys;
+
+    use crate::test_archive::TestDir;
+
+    use super::*;
+
+    #[test]
+    fn read_encrypted_test() -> Result<(), Box<dyn Error>> {
+        // Made up key, the real one is not distributed with bfstool
+        let key = std::array::from_fn(|index| (index as u8).wrapping_mul(7).wrapping_add(3));
+        let temp_dir = TestDir::new("read_encrypted_test")?;
+        let archive_path = temp_dir.join("language.bin");
+        let keys_path = temp_dir.join("keys.toml");
+
+        bfstool::crypt::bzf2001::encrypt_file(
+            "test_data/bzf2001/language.bin".into(),
+            archive_path.clone(),
+            key,
+        )?;
+        fs::write(
+            &keys_path,
+            toml::to_string(&Keys {
+                bzf2001: Some(Bzf2001Keys { key }),
+            })?,
+        )?;
+
+        let archive = read_archive_file(&archive_path, Format::Bzf2001, false, Some(&keys_path))?;
+
+        let expected_archive = bfstool::read_archive_file(
+            &"test_data/bzf2001/language.bin".into(),
+            Format::Bzf2001,
+            false,
+        )?;
+        assert_eq!(archive.file_names(), expected_archive.file_names());
+
+        Ok(())
+    }
+}
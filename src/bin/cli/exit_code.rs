@@ -0,0 +1,108 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::io;
+
+use bfstool::archive_reader::ReadError;
+
+/// Exit code for errors not covered by any other exit code
+pub const GENERAL_ERROR: u8 = 1;
+
+/// Exit code for archives of an invalid or unexpected format
+pub const BAD_FORMAT: u8 = 3;
+
+/// Exit code for files failing CRC32 verification
+pub const CRC_MISMATCH: u8 = 4;
+
+/// Exit code for IO errors
+pub const IO_ERROR: u8 = 5;
+
+/// Exit code for files or archive entries that do not exist
+pub const NOT_FOUND: u8 = 6;
+
+/// Exit code for interrupted operations
+pub const CANCELLED: u8 = 130;
+
+/// A file or archive entry does not exist
+#[derive(Debug)]
+pub struct NotFound(pub String);
+
+impl Display for NotFound {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} not found", self.0)
+    }
+}
+
+impl Error for NotFound {}
+
+/// The given amount of files failed CRC32 verification
+#[derive(Debug)]
+pub struct CrcMismatch(pub usize);
+
+impl Display for CrcMismatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} file(s) failed CRC32 verification", self.0)
+    }
+}
+
+impl Error for CrcMismatch {}
+
+/// Returns the process exit code for the given error
+pub fn exit_code(error: &(dyn Error + 'static)) -> u8 {
+    if let Some(error) = error.downcast_ref::<ReadError>() {
+        match error {
+            ReadError::IoError(error) => io_exit_code(error),
+            _ => BAD_FORMAT,
+        }
+    } else if let Some(error) = error.downcast_ref::<io::Error>() {
+        io_exit_code(error)
+    } else if error.is::<NotFound>() {
+        NOT_FOUND
+    } else if error.is::<CrcMismatch>() {
+        CRC_MISMATCH
+    } else {
+        GENERAL_ERROR
+    }
+}
+
+fn io_exit_code(error: &io::Error) -> u8 {
+    match error.kind() {
+        io::ErrorKind::NotFound => NOT_FOUND,
+        io::ErrorKind::Interrupted => CANCELLED,
+        _ => IO_ERROR,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn exit_code_test() {
+        let errors: [(Box<dyn Error>, u8); 6] = [
+            (
+                Box::new(ReadError::InvalidHashSize {
+                    expected: 0x3E5,
+                    got: 0,
+                }),
+                BAD_FORMAT,
+            ),
+            (
+                Box::new(ReadError::IoError(io::ErrorKind::NotFound.into())),
+                NOT_FOUND,
+            ),
+            (
+                Box::new(io::Error::from(io::ErrorKind::Interrupted)),
+                CANCELLED,
+            ),
+            (Box::new(NotFound("data/menu.bed".to_string())), NOT_FOUND),
+            (Box::new(CrcMismatch(2)), CRC_MISMATCH),
+            ("Unknown error".into(), GENERAL_ERROR),
+        ];
+
+        for (error, expected) in errors {
+            assert_eq!(exit_code(error.as_ref()), expected);
+        }
+    }
+}
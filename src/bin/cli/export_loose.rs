@@ -0,0 +1,201 @@
+use std::collections::HashSet;
+use std::error::Error;
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use clap::Parser;
+use serde::Serialize;
+
+use bfstool::archive_reader::extraction_path;
+use bfstool::compression::JamCrc;
+use bfstool::read_archive_file_with_options;
+
+use crate::config;
+use crate::warning::{check_forced_format, warn_archive};
+
+use super::Format;
+
+/// Name of the manifest written into the output directory
+const MANIFEST_NAME: &str = "overrides.json";
+
+#[derive(Parser)]
+pub struct Arguments {
+    /// BFS archive file name
+    archive: PathBuf,
+    /// Directory with the extracted and modified files of the archive
+    modified: PathBuf,
+    /// Output directory the modified files are exported to, as loose files
+    output: PathBuf,
+    /// Ignore invalid magic/version/hash size
+    #[clap(long)]
+    force: bool,
+    /// BFS archive format, taken from the config file if not provided
+    #[clap(short, long)]
+    format: Option<Format>,
+}
+
+/// Contents of the manifest listing the exported files
+#[derive(Serialize)]
+struct Manifest {
+    /// File name of the archive the files override
+    archive: String,
+    /// Archive paths of all exported files, in file header order
+    overrides: Vec<String>,
+}
+
+pub fn run(arguments: Arguments, mut writer: impl Write) -> Result<(), Box<dyn Error>> {
+    let format = config::format(arguments.format, &arguments.archive)?;
+
+    if arguments.force {
+        check_forced_format(&arguments.archive, format)?;
+    }
+
+    let mut archive = read_archive_file_with_options(
+        &arguments.archive,
+        format,
+        config::read_options(arguments.force),
+    )?;
+    warn_archive(&arguments.archive, archive.as_ref());
+
+    let mut overrides = Vec::new();
+    let mut seen = HashSet::new();
+    for (file_name, file_info) in archive.multiple_file_info(archive.file_names()) {
+        if !seen.insert(file_name.clone()) {
+            continue;
+        }
+        let modified_path = extraction_path(&arguments.modified, &file_name);
+        let Ok(metadata) = fs::metadata(&modified_path) else {
+            continue;
+        };
+        if metadata.len() == file_info.size {
+            let mut archived_crc = JamCrc::new();
+            archive.extract_file_data(&file_info, &mut archived_crc)?;
+            if archived_crc.finalize() == file_crc(&modified_path)? {
+                continue;
+            }
+        }
+
+        let output_path = extraction_path(&arguments.output, &file_name);
+        fs::create_dir_all(output_path.parent().unwrap_or(&arguments.output))?;
+        fs::copy(&modified_path, &output_path)?;
+        overrides.push(file_name);
+    }
+
+    // The manifest is written even if no file was modified, so a manifest left over from an
+    // earlier export does not keep overriding files that were reverted since
+    fs::create_dir_all(&arguments.output)?;
+    let mut manifest = BufWriter::new(File::create(arguments.output.join(MANIFEST_NAME))?);
+    serde_json::to_writer_pretty(
+        &mut manifest,
+        &Manifest {
+            archive: arguments
+                .archive
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string(),
+            overrides: overrides.clone(),
+        },
+    )?;
+    manifest.flush()?;
+
+    writeln!(
+        writer,
+        "Exporting modified files of archive: {}",
+        arguments.archive.to_string_lossy()
+    )?;
+    writeln!(writer, "Exported files: {}", overrides.len())?;
+    for file_name in overrides {
+        writeln!(writer, "{}", file_name)?;
+    }
+
+    Ok(())
+}
+
+/// Calculates the CRC-32/JAMCRC value of a file on disk
+fn file_crc(path: &Path) -> io::Result<u32> {
+    let mut crc = JamCrc::new();
+    io::copy(&mut BufReader::new(File::open(path)?), &mut crc)?;
+    Ok(crc.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::test_archive::TestDir;
+
+    use super::*;
+
+    #[test]
+    fn export_loose_test() -> Result<(), Box<dyn Error>> {
+        let files: [(&[u8], &[u8]); 2] = [
+            (b"data/menu/bg.tm2", b"TIM2"),
+            (b"data/language/version.ini", b"1.0"),
+        ];
+        let data_offset = 0x10
+            + files
+                .iter()
+                .map(|(file_name, _)| 0x13 + file_name.len() as u32)
+                .sum::<u32>();
+
+        // Made up Bzf2002 archive storing uncompressed files
+        let mut archive = Vec::new();
+        archive.extend_from_slice(b"bzf2");
+        archive.extend_from_slice(&0x20021011u32.to_le_bytes());
+        archive.extend_from_slice(&data_offset.to_le_bytes());
+        archive.extend_from_slice(&(files.len() as u32).to_le_bytes());
+        let mut offset = data_offset;
+        for (file_name, data) in files {
+            archive.push(0x00);
+            archive.extend_from_slice(&offset.to_le_bytes());
+            archive.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            archive.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            archive.extend_from_slice(&0u32.to_le_bytes());
+            archive.extend_from_slice(&(file_name.len() as u16).to_le_bytes());
+            archive.extend_from_slice(file_name);
+            offset += data.len() as u32;
+        }
+        for (_, data) in files {
+            archive.extend_from_slice(data);
+        }
+
+        let temp_dir = TestDir::new("export_loose_test")?;
+        let archive_path = temp_dir.join("menu.bzf");
+        let modified = temp_dir.join("modified");
+        let output = temp_dir.join("output");
+        fs::create_dir_all(modified.join("data/menu"))?;
+        fs::create_dir_all(modified.join("data/language"))?;
+        fs::write(&archive_path, &archive)?;
+        fs::write(modified.join("data/menu/bg.tm2"), b"TIM2")?;
+        fs::write(modified.join("data/language/version.ini"), b"1.1")?;
+
+        let arguments = Arguments {
+            archive: archive_path,
+            modified,
+            output: output.clone(),
+            force: false,
+            format: Some(Format::Bzf2002),
+        };
+        run(arguments, io::sink())?;
+
+        let exported = fs::read(output.join("data/language/version.ini"))?;
+        let unchanged_exported = output.join("data/menu/bg.tm2").exists();
+        let manifest = fs::read_to_string(output.join(MANIFEST_NAME))?;
+
+        assert_eq!(exported, b"1.1");
+        assert!(!unchanged_exported);
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&manifest)?,
+            serde_json::json!({
+                "archive": "menu.bzf",
+                "overrides": ["data/language/version.ini"],
+            })
+        );
+
+        Ok(())
+    }
+}
@@ -1,14 +1,30 @@
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
 use std::error::Error;
-use std::path::PathBuf;
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Duration, Instant, UNIX_EPOCH};
 
 use clap::Parser;
-use indicatif::{ProgressBar, ProgressStyle};
+use serde::Serialize;
 
-use bfstool::{read_archive_file, CompressionMethod};
+use bfstool::archive_reader::{extraction_path, open_archive_file, ExtractOptions};
+use bfstool::{read_archive_file_with_options, ArchivedFileInfo, CompressionMethod};
 
-use crate::display::display_size;
+use crate::config;
+use crate::display::{display_crc, display_size};
+use crate::encrypted;
+use crate::file_filter::{parse_size, FileFilter};
+use crate::fix_crc::stored_data_crc;
+use crate::messages;
+use crate::progress::{file_bar, println};
+use crate::warning::{warn, warn_archive};
 
-use super::Format;
+use super::FormatName;
 
 #[derive(Parser)]
 pub struct Arguments {
@@ -17,61 +33,493 @@ pub struct Arguments {
     /// Ignore invalid magic/version/hash size
     #[clap(long)]
     force: bool,
-    /// Output directory
-    output: PathBuf,
+    /// Output directory, taken from the config file if not provided
+    output: Option<PathBuf>,
     /// Print names of extracted files
     #[clap(short, long)]
     verbose: bool,
-    /// BFS archive format
+    /// BFS archive format, taken from the config file if not provided. Can also be the name of a
+    /// registered format provider
     #[clap(short, long)]
-    format: Format,
+    format: Option<FormatName>,
+    /// Keys.toml file name, decrypts the archive in memory before reading it
+    #[clap(long)]
+    keys: Option<PathBuf>,
+    /// Write a JSON line with details about every extracted file to the given file
+    #[clap(long)]
+    audit_log: Option<PathBuf>,
+    /// Check the stored data of every file against its CRC32 value before extracting, and warn
+    /// about mismatching files
+    #[clap(long)]
+    check_crc: bool,
+    /// Extract files sharing the same data as hard links to save disk space
+    #[clap(long)]
+    link_duplicates: bool,
+    /// Set the modification time of extracted files, either to the modification time of the
+    /// archive ("archive") or to the given amount of seconds since the Unix epoch
+    #[clap(long)]
+    mtime: Option<Mtime>,
+    /// Mark extracted files as read-only
+    #[clap(long)]
+    readonly: bool,
+    /// Also extract archives found among the extracted files, into a folder next to them
+    #[clap(long)]
+    recursive: bool,
+    /// Decompress zlib compressed files until their stream ends, ignoring the packed sizes stored
+    /// in the archive, to recover files from archives with damaged headers
+    #[clap(long)]
+    ignore_packed_size: bool,
+    /// Template of the paths files are extracted to inside the output directory, for example
+    /// "{archive_stem}/{path}". Supported placeholders are {archive_name}, {archive_stem},
+    /// {format} and {path}, which must be at the end of the template
+    #[clap(long)]
+    path_template: Option<String>,
+    /// Size of the chunks extracted data is written in, for example "64K", limiting the memory
+    /// used for extraction regardless of the size of the extracted files
+    #[clap(long, value_parser = parse_size)]
+    chunk_size: Option<u64>,
+    /// Limit the rate extracted data is written at to the given amount of bytes per second, for
+    /// example "10M", to avoid saturating shared disks or network shares
+    #[clap(long, value_parser = parse_size)]
+    throttle: Option<u64>,
+    /// Write a JSON file next to every extracted file, named after it with a ".meta.json" suffix,
+    /// recording its flags, compression method, offset, sizes, copies and their offsets and CRC32
+    /// in the archive
+    #[clap(long)]
+    write_metadata: bool,
+    #[clap(flatten)]
+    file_filter: FileFilter,
+}
+
+/// Maximum depth of archives nested inside other archives extracted with `--recursive`
+const MAX_NESTING_DEPTH: usize = 8;
+
+/// Modification time to set on extracted files
+#[derive(Clone)]
+enum Mtime {
+    /// Modification time of the archive
+    Archive,
+    /// Seconds since the Unix epoch
+    Fixed(u64),
+}
+
+impl FromStr for Mtime {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        if value == "archive" {
+            Ok(Mtime::Archive)
+        } else {
+            value.parse().map(Mtime::Fixed).map_err(|_| {
+                format!(
+                    "{} is neither \"archive\" nor an amount of seconds since the Unix epoch",
+                    value
+                )
+            })
+        }
+    }
+}
+
+/// Contents of a metadata file written with `--write-metadata`
+#[derive(Serialize)]
+struct FileMetadata<'a> {
+    name: &'a str,
+    compression_method: String,
+    offset: u64,
+    size: u64,
+    compressed_size: u64,
+    copies: u64,
+    copies_offsets: &'a [u64],
+    /// Raw flags stored in the file header, if the format has them
+    flags: Option<u8>,
+    /// CRC32 value stored in the file header, if the format has one
+    crc32: Option<u32>,
+}
+
+/// A single line of the audit log
+#[derive(Serialize)]
+struct AuditLogEntry<'a> {
+    name: &'a str,
+    offset: u64,
+    size: u64,
+    compressed_size: u64,
+    /// CRC-32 of the extracted file
+    crc32: u32,
+    destination: String,
+    duration_us: u64,
 }
 
 pub fn run(arguments: Arguments) -> Result<(), Box<dyn Error>> {
-    let mut archive =
-        read_archive_file(&arguments.archive, arguments.format.into(), arguments.force)?;
+    let (format_name, mut archive) = encrypted::read_archive_file_with_format_name(
+        &arguments.archive,
+        arguments.format,
+        arguments.force,
+        arguments.keys.as_deref(),
+    )?;
+    let output = config::output(arguments.output, &arguments.archive)?;
+    let output = match &arguments.path_template {
+        Some(path_template) => output.join(expand_path_template(
+            path_template,
+            &arguments.archive,
+            &format_name,
+        )?),
+        None => output,
+    };
 
-    let file_names = archive.file_names();
+    let file_names = archive
+        .multiple_file_info(archive.file_names())
+        .into_iter()
+        .enumerate()
+        .filter(|(index, (_, file_info))| arguments.file_filter.matches(*index, file_info))
+        .map(|(_, (file_name, _))| file_name)
+        .collect::<Vec<String>>();
 
-    let bar = ProgressBar::new(file_names.len() as u64);
+    if arguments.check_crc {
+        check_crc(
+            &arguments.archive,
+            archive.multiple_file_info(file_names.clone()),
+        )?;
+    }
 
-    bar.set_style(
-        ProgressStyle::default_bar()
-            .template("[{elapsed}] {wide_bar} [{pos}/{len}]")
-            .unwrap()
-            .progress_chars("##-"),
-    );
+    let modified = match arguments.mtime {
+        Some(Mtime::Archive) => Some(fs::metadata(&arguments.archive)?.modified()?),
+        Some(Mtime::Fixed(seconds)) => Some(UNIX_EPOCH + Duration::from_secs(seconds)),
+        None => None,
+    };
 
-    archive.extract_files(
+    // Flags of every file by name and offset, as files with the same name can have different flags
+    let file_flags = if arguments.write_metadata {
+        archive
+            .file_flags()
+            .into_iter()
+            .zip(archive.multiple_file_info(archive.file_names()))
+            .map(|((file_name, flags), (_, file_info))| ((file_name, file_info.offset), flags))
+            .collect()
+    } else {
+        HashMap::new()
+    };
+
+    let bar = file_bar(file_names.len() as u64);
+
+    let audit_log = arguments
+        .audit_log
+        .as_ref()
+        .map(|audit_log| File::create(audit_log).map(BufWriter::new))
+        .transpose()?;
+    let audit_log = RefCell::new(audit_log);
+    let callback_error = RefCell::new(None);
+    let extraction_start = Cell::new(Instant::now());
+    let extracted_paths = RefCell::new(Vec::new());
+
+    archive.extract_files_with_options(
         file_names,
-        &arguments.output,
+        &output,
+        ExtractOptions {
+            link_duplicates: arguments.link_duplicates,
+            modified,
+            readonly: arguments.readonly,
+            ignore_packed_size: arguments.ignore_packed_size,
+            chunk_size: arguments.chunk_size.map(|chunk_size| chunk_size as usize),
+            throttle: arguments.throttle,
+            ..Default::default()
+        },
         Box::new(|file_name, file_info| {
+            if let Some(audit_log) = audit_log.borrow_mut().as_mut() {
+                if let Err(error) = write_audit_log_entry(
+                    audit_log,
+                    &output,
+                    file_name,
+                    &file_info,
+                    extraction_start.get().elapsed(),
+                ) {
+                    callback_error.borrow_mut().get_or_insert(error);
+                }
+            }
+            if arguments.write_metadata {
+                let flags = file_flags
+                    .get(&(file_name.to_string(), file_info.offset))
+                    .copied();
+                if let Err(error) = write_metadata(&output, file_name, &file_info, flags) {
+                    callback_error.borrow_mut().get_or_insert(error);
+                }
+            }
             if arguments.verbose {
                 if file_info.compression_method == CompressionMethod::None {
-                    bar.println(format!("{} [{}]", file_name, display_size(&file_info.size)));
+                    println(
+                        &bar,
+                        format!("{} [{}]", file_name, display_size(&file_info.size)),
+                    );
                 } else {
-                    bar.println(format!(
-                        "{} [{} -> {}]",
-                        file_name,
-                        display_size(&file_info.compressed_size),
-                        display_size(&file_info.size)
-                    ));
+                    println(
+                        &bar,
+                        format!(
+                            "{} [{} -> {}]",
+                            file_name,
+                            display_size(&file_info.compressed_size),
+                            display_size(&file_info.size)
+                        ),
+                    );
                 }
             }
+            if arguments.recursive {
+                extracted_paths
+                    .borrow_mut()
+                    .push(extraction_path(&output, file_name));
+            }
             bar.inc(1);
+            extraction_start.set(Instant::now());
         }),
     )?;
 
+    if let Some(error) = callback_error.into_inner() {
+        return Err(error);
+    }
+    if let Some(mut audit_log) = audit_log.into_inner() {
+        audit_log.flush()?;
+    }
+
     bar.finish_and_clear();
 
-    println!(
-        "Extracted {}.",
-        if bar.length() == Some(1) {
-            "1 file".to_string()
-        } else {
-            format!("{} files", bar.length().unwrap_or_default())
+    if bar.length() == Some(1) {
+        println!("{}", messages::message("extract.extracted_file", &[]));
+    } else {
+        println!(
+            "{}",
+            messages::message(
+                "extract.extracted_files",
+                &[&bar.length().unwrap_or_default()]
+            )
+        );
+    }
+
+    if arguments.recursive {
+        let nested_archives = extract_nested(extracted_paths.into_inner(), 0)?;
+        println!(
+            "{}",
+            messages::message("extract.nested_archives", &[&nested_archives])
+        );
+    }
+
+    Ok(())
+}
+
+/// Expands the placeholders of a path template, returning the directory the archived file paths
+/// are appended to
+fn expand_path_template(
+    path_template: &str,
+    archive: &Path,
+    format_name: &str,
+) -> Result<PathBuf, String> {
+    let Some(directory) = path_template.strip_suffix("{path}") else {
+        return Err(format!(
+            "Path template {} does not end with {{path}}",
+            path_template
+        ));
+    };
+    let archive_name = archive.file_name().unwrap_or_default().to_string_lossy();
+    let archive_stem = archive.file_stem().unwrap_or_default().to_string_lossy();
+    Ok(PathBuf::from(
+        directory
+            .replace("{archive_name}", &archive_name)
+            .replace("{archive_stem}", &archive_stem)
+            .replace("{format}", format_name),
+    ))
+}
+
+/// Extracts every archive among the given extracted files into `<file>.extracted`, recursively
+///
+/// Returns the amount of extracted archives
+fn extract_nested(extracted_paths: Vec<PathBuf>, depth: usize) -> Result<u64, Box<dyn Error>> {
+    let mut nested_archives = 0;
+    for path in extracted_paths {
+        let Some(format) = bfstool::probe(&mut BufReader::new(File::open(&path)?)) else {
+            continue;
+        };
+        if depth == MAX_NESTING_DEPTH {
+            warn(format!(
+                "{} is nested too deeply and will not be extracted",
+                path.to_string_lossy()
+            ));
+            continue;
+        }
+        let mut archive =
+            match read_archive_file_with_options(&path, format, config::read_options(false)) {
+                Ok(archive) => archive,
+                Err(error) => {
+                    warn(format!(
+                        "{} looks like a {} archive, but can not be read: {}",
+                        path.to_string_lossy(),
+                        format,
+                        error
+                    ));
+                    continue;
+                }
+            };
+        warn_archive(&path, archive.as_ref());
+
+        let mut output = path.clone().into_os_string();
+        output.push(".extracted");
+        let output = PathBuf::from(output);
+
+        let file_names = archive.file_names();
+        let nested_paths = RefCell::new(Vec::new());
+        archive.extract_files(
+            file_names,
+            &output,
+            Box::new(|file_name, _| {
+                nested_paths
+                    .borrow_mut()
+                    .push(extraction_path(&output, file_name))
+            }),
+        )?;
+        nested_archives += 1 + extract_nested(nested_paths.into_inner(), depth + 1)?;
+    }
+
+    Ok(nested_archives)
+}
+
+/// Warns about every file whose stored data does not match its CRC32 value
+fn check_crc(archive: &Path, files: Vec<(String, ArchivedFileInfo)>) -> io::Result<()> {
+    let mut reader = BufReader::new(open_archive_file(archive)?);
+    for (file_name, file_info) in files {
+        let Some(stored_crc) = file_info.hash else {
+            continue;
+        };
+        let actual_crc = stored_data_crc(&mut reader, &file_name, &file_info)?;
+        if actual_crc != stored_crc {
+            warn(format!(
+                "{}: CRC32 mismatch, stored {} but the data has {}",
+                file_name,
+                display_crc(&stored_crc),
+                display_crc(&actual_crc)
+            ));
         }
-    );
+    }
+    Ok(())
+}
+
+/// Writes the metadata of an extracted file next to it, see [FileMetadata]
+fn write_metadata(
+    output: &Path,
+    file_name: &str,
+    file_info: &ArchivedFileInfo,
+    flags: Option<u8>,
+) -> Result<(), Box<dyn Error>> {
+    let mut metadata_path = extraction_path(output, file_name).into_os_string();
+    metadata_path.push(".meta.json");
+
+    let metadata_file = BufWriter::new(File::create(metadata_path)?);
+    serde_json::to_writer_pretty(
+        metadata_file,
+        &FileMetadata {
+            name: file_name,
+            compression_method: file_info.compression_method.to_string(),
+            offset: file_info.offset,
+            size: file_info.size,
+            compressed_size: file_info.compressed_size,
+            copies: file_info.copies,
+            copies_offsets: &file_info.copies_offsets,
+            flags,
+            crc32: file_info.hash,
+        },
+    )?;
 
     Ok(())
 }
+
+fn write_audit_log_entry(
+    audit_log: &mut impl Write,
+    output: &Path,
+    file_name: &str,
+    file_info: &ArchivedFileInfo,
+    duration: Duration,
+) -> Result<(), Box<dyn Error>> {
+    let destination = extraction_path(output, file_name);
+
+    let mut extracted_file = File::open(&destination)?;
+    let mut hasher = crc32fast::Hasher::new();
+    let mut buffer = [0; 0x8000];
+    loop {
+        let read = extracted_file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+
+    serde_json::to_writer(
+        &mut *audit_log,
+        &AuditLogEntry {
+            name: file_name,
+            offset: file_info.offset,
+            size: file_info.size,
+            compressed_size: file_info.compressed_size,
+            crc32: hasher.finalize(),
+            destination: destination.to_string_lossy().to_string(),
+            duration_us: duration.as_micros() as u64,
+        },
+    )?;
+    writeln!(audit_log)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::test_archive::TestDir;
+
+    use super::*;
+
+    #[test]
+    fn path_template_test() {
+        let archive = Path::new("games/FlatOut/common1.bfs");
+
+        assert_eq!(
+            expand_path_template("{archive_stem}/{path}", archive, "bfs2004a"),
+            Ok(PathBuf::from("common1/"))
+        );
+        assert_eq!(
+            expand_path_template("{format}/{archive_name}_files/{path}", archive, "bfs2004a"),
+            Ok(PathBuf::from("bfs2004a/common1.bfs_files/"))
+        );
+        assert!(expand_path_template("{path}/{archive_stem}", archive, "bfs2004a").is_err());
+    }
+
+    #[test]
+    fn write_metadata_test() -> Result<(), Box<dyn Error>> {
+        let temp_dir = TestDir::new("write_metadata_test")?;
+        let output = &temp_dir.path;
+        fs::create_dir_all(output.join("data/language"))?;
+        let file_info = ArchivedFileInfo {
+            offset: 0xFDC,
+            compression_method: CompressionMethod::Zlib,
+            size: 0xF5F,
+            compressed_size: 0x1D7,
+            copies: 1,
+            copies_offsets: vec![0x11B3],
+            hash: Some(0x6E3C7E35),
+        };
+        write_metadata(output, "data/language/version.ini", &file_info, Some(0x05))?;
+
+        let metadata = fs::read_to_string(output.join("data/language/version.ini.meta.json"))?;
+        let metadata: serde_json::Value = serde_json::from_str(&metadata)?;
+        assert_eq!(
+            metadata,
+            serde_json::json!({
+                "name": "data/language/version.ini",
+                "compression_method": "zlib",
+                "offset": 0xFDC,
+                "size": 0xF5F,
+                "compressed_size": 0x1D7,
+                "copies": 1,
+                "copies_offsets": [0x11B3],
+                "flags": 0x05,
+                "crc32": 0x6E3C7E35u32,
+            })
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,180 @@
+use std::ops::Range;
+
+use clap::{Args, ValueEnum};
+
+use bfstool::{ArchivedFileInfo, CompressionMethod};
+
+/// Options selecting which archived files a command works on
+#[derive(Args, Default)]
+pub struct FileFilter {
+    /// Only include files at least this large after unpacking, in bytes or with a K, M or G suffix
+    #[clap(long, value_parser = parse_size)]
+    min_size: Option<u64>,
+    /// Only include files at most this large after unpacking, in bytes or with a K, M or G suffix
+    #[clap(long, value_parser = parse_size)]
+    max_size: Option<u64>,
+    /// Only include files at least this large in the archive, in bytes or with a K, M or G suffix
+    #[clap(long, value_parser = parse_size)]
+    min_compressed_size: Option<u64>,
+    /// Only include files at most this large in the archive, in bytes or with a K, M or G suffix
+    #[clap(long, value_parser = parse_size)]
+    max_compressed_size: Option<u64>,
+    /// Only include files stored with the given compression method, can be given multiple times
+    #[clap(long)]
+    method: Vec<Method>,
+    /// Only include files with their header index in the given range, for example "100..200"
+    /// (excluding 200), "100.." or "..200". Indexes start at 0 and follow the file header order
+    #[clap(long, value_parser = parse_index_range)]
+    index_range: Option<Range<usize>>,
+}
+
+/// Compression method to filter files by
+#[derive(ValueEnum, Clone, Copy, Eq, PartialEq)]
+pub enum Method {
+    #[value(alias = "store")]
+    None,
+    Zlib,
+    Zstd,
+}
+
+impl From<Method> for CompressionMethod {
+    fn from(value: Method) -> Self {
+        match value {
+            Method::None => CompressionMethod::None,
+            Method::Zlib => CompressionMethod::Zlib,
+            Method::Zstd => CompressionMethod::Zstd,
+        }
+    }
+}
+
+impl FileFilter {
+    /// Checks whether the file with the given header index is included by all given options
+    pub fn matches(&self, index: usize, file_info: &ArchivedFileInfo) -> bool {
+        self.index_range
+            .as_ref()
+            .is_none_or(|index_range| index_range.contains(&index))
+            && self.min_size.is_none_or(|size| file_info.size >= size)
+            && self.max_size.is_none_or(|size| file_info.size <= size)
+            && self
+                .min_compressed_size
+                .is_none_or(|size| file_info.compressed_size >= size)
+            && self
+                .max_compressed_size
+                .is_none_or(|size| file_info.compressed_size <= size)
+            && (self.method.is_empty()
+                || self
+                    .method
+                    .iter()
+                    .any(|method| CompressionMethod::from(*method) == file_info.compression_method))
+    }
+}
+
+/// Parses a size in bytes, optionally followed by a binary K, M or G suffix
+pub fn parse_size(value: &str) -> Result<u64, String> {
+    let (number, multiplier) = match value
+        .chars()
+        .last()
+        .map(|suffix| suffix.to_ascii_uppercase())
+    {
+        Some('K') => (&value[..value.len() - 1], 1 << 10),
+        Some('M') => (&value[..value.len() - 1], 1 << 20),
+        Some('G') => (&value[..value.len() - 1], 1 << 30),
+        _ => (value, 1),
+    };
+    number
+        .parse::<u64>()
+        .ok()
+        .and_then(|number| number.checked_mul(multiplier))
+        .ok_or_else(|| format!("{} is not a valid size", value))
+}
+
+/// Parses a range of header indexes in the `start..end` form, either bound can be left out
+fn parse_index_range(value: &str) -> Result<Range<usize>, String> {
+    let error = || format!("{} is not a valid index range", value);
+    let (start, end) = value.split_once("..").ok_or_else(error)?;
+    let start = if start.is_empty() {
+        0
+    } else {
+        start.parse().map_err(|_| error())?
+    };
+    let end = if end.is_empty() {
+        usize::MAX
+    } else {
+        end.parse().map_err(|_| error())?
+    };
+    Ok(start..end)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn parse_size_test() {
+        assert_eq!(parse_size("512"), Ok(512));
+        assert_eq!(parse_size("4k"), Ok(0x1000));
+        assert_eq!(parse_size("16M"), Ok(0x1000000));
+        assert_eq!(parse_size("1G"), Ok(0x40000000));
+        assert!(parse_size("1.5M").is_err());
+        assert!(parse_size("M").is_err());
+    }
+
+    #[test]
+    fn parse_index_range_test() {
+        assert_eq!(parse_index_range("100..200"), Ok(100..200));
+        assert_eq!(parse_index_range("100.."), Ok(100..usize::MAX));
+        assert_eq!(parse_index_range("..200"), Ok(0..200));
+        assert!(parse_index_range("100").is_err());
+        assert!(parse_index_range("a..b").is_err());
+    }
+
+    #[test]
+    fn matches_test() {
+        let file_info = ArchivedFileInfo {
+            compression_method: CompressionMethod::Zlib,
+            size: 0xF5F,
+            compressed_size: 0x78D,
+            ..Default::default()
+        };
+
+        assert!(FileFilter::default().matches(0, &file_info));
+        assert!(FileFilter {
+            min_size: Some(0xF5F),
+            max_compressed_size: Some(0x800),
+            ..Default::default()
+        }
+        .matches(0, &file_info));
+        assert!(!FileFilter {
+            max_size: Some(0x800),
+            ..Default::default()
+        }
+        .matches(0, &file_info));
+        assert!(!FileFilter {
+            min_compressed_size: Some(0x800),
+            ..Default::default()
+        }
+        .matches(0, &file_info));
+        assert!(FileFilter {
+            method: vec![Method::Zlib, Method::Zstd],
+            ..Default::default()
+        }
+        .matches(0, &file_info));
+        assert!(!FileFilter {
+            method: vec![Method::None],
+            ..Default::default()
+        }
+        .matches(0, &file_info));
+        assert!(FileFilter {
+            index_range: Some(100..200),
+            ..Default::default()
+        }
+        .matches(100, &file_info));
+        assert!(!FileFilter {
+            index_range: Some(100..200),
+            ..Default::default()
+        }
+        .matches(200, &file_info));
+    }
+}
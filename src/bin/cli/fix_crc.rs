@@ -0,0 +1,192 @@
+use std::error::Error;
+use std::io;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+
+use clap::Parser;
+use tabled::settings::object::{Columns, Segment};
+use tabled::settings::{Alignment, Modify, Style};
+use tabled::{Table, Tabled};
+
+use bfstool::compression::JamCrc;
+use bfstool::{read_archive_file_with_options, ArchivedFileInfo};
+
+use crate::config;
+use crate::display::{display_crc, display_offset};
+use crate::safe_write::modify_file;
+use crate::warning::{check_forced_format, warn_archive};
+
+use super::Format;
+
+#[derive(Parser)]
+pub struct Arguments {
+    /// BFS archive file name
+    archive: PathBuf,
+    /// Ignore invalid magic/version/hash size
+    #[clap(long)]
+    force: bool,
+    /// BFS archive format, taken from the config file if not provided
+    #[clap(short, long)]
+    format: Option<Format>,
+    /// Only report the CRC32 values that would change without writing them
+    #[clap(long)]
+    dry_run: bool,
+    /// Keep the original archive as a backup, which can be restored with the restore command
+    #[clap(long)]
+    backup: bool,
+}
+
+#[derive(Tabled)]
+struct ChangedCrc {
+    #[tabled(rename = "Old CRC", display_with = "display_crc")]
+    old: u32,
+
+    #[tabled(rename = "New CRC", display_with = "display_crc")]
+    new: u32,
+
+    #[tabled(rename = "Offset", display_with = "display_offset")]
+    offset: u64,
+
+    #[tabled(rename = "File Name")]
+    file_name: String,
+
+    #[tabled(skip)]
+    hash_field_offset: u64,
+}
+
+pub fn run(arguments: Arguments, mut writer: impl Write) -> Result<(), Box<dyn Error>> {
+    let format = config::format(arguments.format, &arguments.archive)?;
+
+    if arguments.force {
+        check_forced_format(&arguments.archive, format)?;
+    }
+
+    let mut archive = read_archive_file_with_options(
+        &arguments.archive,
+        format,
+        config::read_options(arguments.force),
+    )?;
+    warn_archive(&arguments.archive, archive.as_ref());
+
+    let hash_fields = archive.hash_field_offsets();
+    let checked = hash_fields.len();
+
+    let mut changed = Vec::new();
+    for (file_name, hash_field_offset, file_info) in hash_fields {
+        let old = file_info.hash.unwrap_or_default();
+        let new = stored_data_crc(archive.reader(), &file_name, &file_info)?;
+        if old != new {
+            changed.push(ChangedCrc {
+                old,
+                new,
+                offset: file_info.offset,
+                file_name,
+                hash_field_offset,
+            });
+        }
+    }
+
+    drop(archive);
+
+    if !arguments.dry_run && !changed.is_empty() {
+        modify_file(&arguments.archive, arguments.backup, |file| {
+            for changed_crc in &changed {
+                file.seek(SeekFrom::Start(changed_crc.hash_field_offset))?;
+                file.write_all(&changed_crc.new.to_le_bytes())?;
+            }
+            Ok(())
+        })?;
+    }
+
+    writeln!(
+        writer,
+        "{} archive: {}",
+        if arguments.dry_run {
+            "Checking"
+        } else {
+            "Fixing"
+        },
+        arguments.archive.to_string_lossy()
+    )?;
+    writeln!(writer, "Checked CRC32 values: {}", checked)?;
+    writeln!(writer, "Changed CRC32 values: {}", changed.len())?;
+    if !changed.is_empty() {
+        writeln!(
+            writer,
+            "{}",
+            Table::new(changed)
+                .with(Style::markdown())
+                .with(Modify::new(Segment::all()).with(Alignment::right()))
+                .with(Modify::new(Columns::single(2)).with(Alignment::center()))
+                .with(Modify::new(Columns::last()).with(Alignment::left()))
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Calculates the CRC-32/JAMCRC value of the stored (compressed) data of a file, as stored in the
+/// file headers of formats with CRC32 values
+pub fn stored_data_crc(
+    reader: &mut (impl Read + Seek),
+    file_name: &str,
+    file_info: &ArchivedFileInfo,
+) -> io::Result<u32> {
+    reader.seek(SeekFrom::Start(file_info.offset))?;
+    let mut data = reader.take(file_info.compressed_size);
+
+    let mut hasher = JamCrc::new();
+    let mut buffer = [0; 0x8000];
+    let mut total_read = 0;
+    loop {
+        let read = data.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        total_read += read as u64;
+        hasher.update(&buffer[..read]);
+    }
+    if total_read != file_info.compressed_size {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            format!("Archive ends before the end of {}", file_name),
+        ));
+    }
+
+    Ok(hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use pretty_assertions::assert_eq;
+
+    use crate::test_archive::TestArchive;
+
+    use super::*;
+
+    #[test]
+    fn fix_crc_test() -> Result<(), Box<dyn Error>> {
+        let archive = TestArchive::europe(&[])?;
+        let archive_data = &archive.data;
+
+        let arguments = Arguments {
+            archive: archive.path.clone(),
+            force: false,
+            format: Some(Format::Bfs2004a),
+            dry_run: false,
+            backup: false,
+        };
+        run(arguments, io::sink())?;
+
+        let fixed_data = fs::read(&archive.path)?;
+
+        let expected_crc = bfstool::compression::jamcrc(&[0; 0x1D7]);
+        assert_eq!(&fixed_data[0xFBC..0xFC0], &expected_crc.to_le_bytes());
+        assert_eq!(&fixed_data[..0xFBC], &archive_data[..0xFBC]);
+        assert_eq!(&fixed_data[0xFC0..], &archive_data[0xFC0..]);
+
+        Ok(())
+    }
+}
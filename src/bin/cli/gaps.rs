@@ -0,0 +1,216 @@
+use std::error::Error;
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::{BufReader, Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+
+use clap::Parser;
+use tabled::settings::object::{Columns, Segment};
+use tabled::settings::{Alignment, Modify, Style};
+use tabled::{Table, Tabled};
+
+use bfstool::archive_reader::ArchiveReader;
+use bfstool::layout::analyze_layout;
+use bfstool::read_archive_file_with_options;
+
+use crate::config;
+use crate::display::{display_offset, display_size};
+use crate::warning::{check_forced_format, warn_archive};
+
+use super::Format;
+
+#[derive(Parser)]
+pub struct Arguments {
+    /// BFS archive file name
+    archive: PathBuf,
+    /// Ignore invalid magic/version/hash size
+    #[clap(long)]
+    force: bool,
+    /// BFS archive format, taken from the config file if not provided
+    #[clap(short, long)]
+    format: Option<Format>,
+}
+
+#[derive(Tabled, Debug, Eq, PartialEq)]
+struct LayoutRegion {
+    #[tabled(rename = "Kind")]
+    kind: String,
+
+    #[tabled(rename = "Start", display_with = "display_offset")]
+    start: u64,
+
+    #[tabled(rename = "End", display_with = "display_offset")]
+    end: u64,
+
+    #[tabled(rename = "Size", display_with = "display_size")]
+    size: u64,
+}
+
+pub fn run(arguments: Arguments, mut writer: impl Write) -> Result<(), Box<dyn Error>> {
+    let format = config::format(arguments.format, &arguments.archive)?;
+
+    if arguments.force {
+        check_forced_format(&arguments.archive, format)?;
+    }
+
+    let physical_size = fs::metadata(&arguments.archive)?.len();
+    let mut archive = read_archive_file_with_options(
+        &arguments.archive,
+        format,
+        config::read_options(arguments.force),
+    )?;
+    warn_archive(&arguments.archive, archive.as_ref());
+
+    let regions = find_regions(archive.as_mut(), physical_size)?;
+
+    let total_size = |kind: &str| {
+        regions
+            .iter()
+            .filter(|region| region.kind == kind)
+            .map(|region| region.size)
+            .sum::<u64>()
+    };
+
+    writeln!(
+        writer,
+        "Analyzing archive: {}",
+        arguments.archive.to_string_lossy()
+    )?;
+    writeln!(writer, "Physical size: {}", display_size(&physical_size))?;
+    writeln!(
+        writer,
+        "Header end: {}",
+        display_offset(&archive.header_size())
+    )?;
+    writeln!(writer, "Padding: {}", display_size(&total_size("padding")))?;
+    writeln!(
+        writer,
+        "Unreferenced data: {}",
+        display_size(&total_size("data"))
+    )?;
+    writeln!(
+        writer,
+        "Overlapping: {}",
+        display_size(&total_size("overlap"))
+    )?;
+    if !regions.is_empty() {
+        writeln!(
+            writer,
+            "{}",
+            Table::new(regions)
+                .with(Style::markdown())
+                .with(Modify::new(Segment::all()).with(Alignment::right()))
+                .with(Modify::new(Columns::new(1..3)).with(Alignment::center()))
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Finds all gaps, overlapping and shared regions between the end of the headers and the end of
+/// the archive file
+///
+/// Gaps containing only zeroes are reported as padding, others as unreferenced data
+fn find_regions(
+    archive: &mut dyn ArchiveReader<BufReader<File>>,
+    physical_size: u64,
+) -> io::Result<Vec<LayoutRegion>> {
+    let file_info = archive.multiple_file_info(archive.file_names());
+    let report = analyze_layout(
+        archive.header_size(),
+        physical_size,
+        file_info.iter().flat_map(|(_, info)| {
+            std::iter::once(info.offset)
+                .chain(info.copies_offsets.iter().copied())
+                .map(|offset| offset..offset + info.compressed_size)
+        }),
+    );
+
+    let mut regions = Vec::new();
+    for gap in report.gaps {
+        let reader = archive.reader();
+        reader.seek(SeekFrom::Start(gap.start))?;
+        let mut data = reader.take(gap.end - gap.start);
+        let mut buffer = [0; 0x8000];
+        let mut zeroed = true;
+        loop {
+            let read = data.read(&mut buffer)?;
+            if read == 0 {
+                break;
+            }
+            if buffer[..read].iter().any(|byte| *byte != 0) {
+                zeroed = false;
+                break;
+            }
+        }
+        regions.push(LayoutRegion {
+            kind: if zeroed { "padding" } else { "data" }.to_string(),
+            start: gap.start,
+            end: gap.end,
+            size: gap.end - gap.start,
+        });
+    }
+    regions.extend(report.overlaps.into_iter().map(|overlap| LayoutRegion {
+        kind: "overlap".to_string(),
+        start: overlap.start,
+        end: overlap.end,
+        size: overlap.end - overlap.start,
+    }));
+    regions.extend(
+        report
+            .shared
+            .into_iter()
+            .map(|(shared, count)| LayoutRegion {
+                kind: format!("shared by {}", count),
+                start: shared.start,
+                end: shared.end,
+                size: shared.end - shared.start,
+            }),
+    );
+    regions.sort_by_key(|region| (region.start, region.end));
+
+    Ok(regions)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use bfstool::read_archive_file;
+
+    use crate::test_archive::TestArchive;
+
+    use super::*;
+
+    #[test]
+    fn find_regions_test() -> Result<(), Box<dyn Error>> {
+        // Trailing gap after the file data
+        let mut trailing = [0; 0x10];
+        trailing[0x8] = 0xFF;
+        let test_archive = TestArchive::europe(&trailing)?;
+
+        let mut archive = read_archive_file(&test_archive.path, bfstool::Format::Bfs2004a, false)?;
+        let result = find_regions(archive.as_mut(), test_archive.data.len() as u64);
+
+        assert_eq!(
+            result?,
+            vec![
+                LayoutRegion {
+                    kind: "padding".to_string(),
+                    start: 0xFDB,
+                    end: 0xFDC,
+                    size: 0x1,
+                },
+                LayoutRegion {
+                    kind: "data".to_string(),
+                    start: 0xFDC + 0x1D7,
+                    end: 0xFDC + 0x1D7 + 0x10,
+                    size: 0x10,
+                },
+            ]
+        );
+
+        Ok(())
+    }
+}
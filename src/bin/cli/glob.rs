@@ -0,0 +1,56 @@
+/// Checks whether the file name matches a simple glob pattern
+///
+/// `*` matches any sequence of characters including `/`, `?` matches any single character.
+/// Matching is ASCII case-insensitive, as the games treat file names that way.
+pub fn glob_match(pattern: &str, file_name: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<char>>();
+    let file_name = file_name.chars().collect::<Vec<char>>();
+
+    let mut pattern_index = 0;
+    let mut file_name_index = 0;
+    // Position of the last `*` in the pattern and the file name position it was tried at
+    let mut backtrack = None;
+    while file_name_index < file_name.len() {
+        match pattern.get(pattern_index) {
+            Some('*') => {
+                backtrack = Some((pattern_index, file_name_index));
+                pattern_index += 1;
+            }
+            Some(character)
+                if *character == '?'
+                    || character.eq_ignore_ascii_case(&file_name[file_name_index]) =>
+            {
+                pattern_index += 1;
+                file_name_index += 1;
+            }
+            _ => match backtrack {
+                Some((star_index, star_file_name_index)) => {
+                    backtrack = Some((star_index, star_file_name_index + 1));
+                    pattern_index = star_index + 1;
+                    file_name_index = star_file_name_index + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[pattern_index..]
+        .iter()
+        .all(|character| *character == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glob_match_test() {
+        assert!(glob_match("*.dds", "data/menu/border_up.dds"));
+        assert!(glob_match("data/menu/*", "data/menu/border_up.dds"));
+        assert!(glob_match("*BORDER_??.DDS", "data/menu/border_up.dds"));
+        assert!(glob_match("*", ""));
+        assert!(!glob_match("*.dds", "data/menu/border_up.tga"));
+        assert!(!glob_match("border_?.dds", "border_up.dds"));
+        assert!(!glob_match("data/*/x.dds", "data/menu/border_up.dds"));
+    }
+}
@@ -0,0 +1,190 @@
+use std::error::Error;
+use std::fs;
+use std::io::{BufReader, Write};
+use std::path::PathBuf;
+
+use clap::Parser;
+use tabled::settings::object::Segment;
+use tabled::settings::{Alignment, Modify, Style};
+use tabled::{Table, Tabled};
+
+use bfstool::archive_reader::open_archive_file;
+use bfstool::{read_archive_file_with_options, CompressionMethod};
+
+use crate::config;
+use crate::display::{display_offset, display_size};
+use crate::probe::write_header_fields;
+use crate::warning::{check_forced_format, warn_archive};
+
+use super::Format;
+
+#[derive(Parser)]
+pub struct Arguments {
+    /// BFS archive file name
+    archive: PathBuf,
+    /// Ignore invalid magic/version/hash size
+    #[clap(long)]
+    force: bool,
+    /// BFS archive format, detected automatically if not provided
+    #[clap(short, long)]
+    format: Option<Format>,
+}
+
+#[derive(Tabled)]
+struct MethodSummary {
+    #[tabled(rename = "Method")]
+    method: CompressionMethod,
+
+    #[tabled(rename = "Files")]
+    files: u64,
+
+    #[tabled(rename = "Size", display_with = "display_size")]
+    size: u64,
+
+    #[tabled(rename = "Compressed", display_with = "display_size")]
+    compressed: u64,
+}
+
+pub fn run(arguments: Arguments, mut writer: impl Write) -> Result<(), Box<dyn Error>> {
+    let mut reader = BufReader::new(open_archive_file(&arguments.archive)?);
+
+    let format: bfstool::Format = match arguments.format {
+        Some(format) => {
+            if arguments.force {
+                check_forced_format(&arguments.archive, format.clone().into())?;
+            }
+            format.into()
+        }
+        None => bfstool::probe(&mut reader).ok_or("Unable to detect the archive format")?,
+    };
+
+    let archive = read_archive_file_with_options(
+        &arguments.archive,
+        format,
+        config::read_options(arguments.force),
+    )?;
+    warn_archive(&arguments.archive, archive.as_ref());
+    let file_info = archive.multiple_file_info(archive.file_names());
+
+    let mut methods = vec![
+        CompressionMethod::None,
+        CompressionMethod::Zlib,
+        CompressionMethod::Zstd,
+    ];
+    for (_, file_info) in &file_info {
+        if !methods.contains(&file_info.compression_method) {
+            methods.push(file_info.compression_method);
+        }
+    }
+
+    let method_summaries = methods
+        .into_iter()
+        .filter_map(|method| {
+            let method_file_info = file_info
+                .iter()
+                .filter(|(_, file_info)| file_info.compression_method == method)
+                .collect::<Vec<_>>();
+            if method_file_info.is_empty() {
+                None
+            } else {
+                Some(MethodSummary {
+                    method,
+                    files: method_file_info.len() as u64,
+                    size: method_file_info
+                        .iter()
+                        .map(|(_, file_info)| file_info.size)
+                        .sum(),
+                    compressed: method_file_info
+                        .iter()
+                        .map(|(_, file_info)| file_info.compressed_size)
+                        .sum(),
+                })
+            }
+        })
+        .collect::<Vec<MethodSummary>>();
+
+    writeln!(writer, "Archive: {}", arguments.archive.to_string_lossy())?;
+    writeln!(
+        writer,
+        "Physical size: {}",
+        display_size(&fs::metadata(&arguments.archive)?.len())
+    )?;
+    writeln!(writer, "Format: {}", format)?;
+    write_header_fields(&mut writer, &mut reader, format)?;
+    if let Some(data_offset) = file_info
+        .iter()
+        .map(|(_, file_info)| file_info.offset)
+        .min()
+    {
+        writeln!(writer, "Data offset: {}", display_offset(&data_offset))?;
+    }
+    writeln!(
+        writer,
+        "Total size: {}",
+        display_size(
+            &file_info
+                .iter()
+                .map(|(_, file_info)| file_info.size)
+                .sum::<u64>()
+        )
+    )?;
+    writeln!(
+        writer,
+        "Total compressed size: {}",
+        display_size(
+            &file_info
+                .iter()
+                .map(|(_, file_info)| file_info.compressed_size)
+                .sum::<u64>()
+        )
+    )?;
+    writeln!(
+        writer,
+        "{}",
+        Table::new(method_summaries)
+            .with(Style::markdown())
+            .with(Modify::new(Segment::all()).with(Alignment::right()))
+    )?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+    use std::io::Read;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn info_test() -> Result<(), Box<dyn Error>> {
+        let mut result = Vec::new();
+        let arguments = Arguments {
+            archive: PathBuf::from("test_data/bfs2004a/europe.bin"),
+            force: false,
+            format: None,
+        };
+        run(arguments, &mut result)?;
+
+        let mut expected_result_file = File::open("test_data/cli/info.txt")?;
+        let mut expected_result = Vec::new();
+        expected_result_file.read_to_end(&mut expected_result)?;
+
+        // Compare results as strings for pretty diff when mismatching
+        //
+        // Ignore mismatching line breaks when comparing (assume \r\n and \n are equal) by
+        // removing all occurrences of \r
+        assert_eq!(
+            String::from_utf8_lossy(&result)
+                .to_string()
+                .replace('\r', ""),
+            String::from_utf8_lossy(&expected_result)
+                .to_string()
+                .replace('\r', "")
+        );
+
+        Ok(())
+    }
+}
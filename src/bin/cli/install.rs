@@ -0,0 +1,216 @@
+use std::collections::HashSet;
+use std::error::Error;
+use std::fs;
+use std::io;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use clap::Parser;
+
+use crate::exit_code::NotFound;
+use crate::messages::message;
+use crate::safe_write::replace_file;
+use crate::sign::hash_file;
+
+#[derive(Parser)]
+pub struct Arguments {
+    /// Archive file name
+    archive: PathBuf,
+    /// Game installation directory, the archive replaces the archive with the same file name in it
+    game_dir: PathBuf,
+    /// Path of the replaced archive inside the game directory, if it can not be found by its file
+    /// name or multiple archives share it
+    #[clap(long)]
+    target: Option<PathBuf>,
+}
+
+pub fn run(arguments: Arguments, mut writer: impl Write) -> Result<(), Box<dyn Error>> {
+    let destination = match &arguments.target {
+        Some(target) => arguments.game_dir.join(target),
+        None => find_destination(&arguments.archive, &arguments.game_dir)?,
+    };
+
+    let hash = hash_file(&arguments.archive)?;
+    let backup_path = replace_file(&destination, &arguments.archive, |copy| {
+        if hash_file(copy)? == hash {
+            Ok(())
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "Copy of {} does not match the original",
+                    arguments.archive.to_string_lossy()
+                ),
+            ))
+        }
+    })?;
+
+    writeln!(
+        writer,
+        "{}",
+        message(
+            "install.installed",
+            &[
+                &arguments.archive.to_string_lossy(),
+                &destination.to_string_lossy()
+            ]
+        )
+    )?;
+    writeln!(writer, "BLAKE3: {}", hash.to_hex())?;
+    match backup_path {
+        Some(backup_path) => writeln!(
+            writer,
+            "{}",
+            message("install.backup", &[&backup_path.to_string_lossy()])
+        )?,
+        None => writeln!(writer, "{}", message("install.no_backup", &[]))?,
+    }
+
+    Ok(())
+}
+
+/// Finds the only file in the game directory and its subdirectories with the same file name as
+/// the archive, ignoring case
+fn find_destination(archive: &Path, game_dir: &Path) -> Result<PathBuf, Box<dyn Error>> {
+    let file_name = archive
+        .file_name()
+        .ok_or("Archive has no file name")?
+        .to_string_lossy()
+        .to_lowercase();
+    let mut found = find_files(game_dir, &file_name)?;
+    match found.len() {
+        0 => Err(NotFound(format!("{} in {}", file_name, game_dir.to_string_lossy())).into()),
+        1 => Ok(found.remove(0)),
+        _ => Err(format!(
+            "Multiple archives named {} found, select one with --target: {}",
+            file_name,
+            found
+                .iter()
+                .map(|path| path.to_string_lossy())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+        .into()),
+    }
+}
+
+/// Finds all files with the given lowercase file name in the directory and its subdirectories,
+/// sorted by path
+fn find_files(directory: &Path, file_name: &str) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    find_files_in(directory, file_name, &mut HashSet::new(), &mut files)?;
+    files.sort();
+
+    Ok(files)
+}
+
+/// Adds the files with the given lowercase file name in the directory and its subdirectories to
+/// `files`, skipping directories already visited through a symbolic link
+fn find_files_in(
+    directory: &Path,
+    file_name: &str,
+    visited: &mut HashSet<PathBuf>,
+    files: &mut Vec<PathBuf>,
+) -> io::Result<()> {
+    if !visited.insert(fs::canonicalize(directory)?) {
+        return Ok(());
+    }
+    for entry in fs::read_dir(directory)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            find_files_in(&path, file_name, visited, files)?;
+        } else if path
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().to_lowercase() == file_name)
+        {
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::safe_write::{uninstall_file, Uninstalled};
+    use crate::test_archive::TestDir;
+
+    use super::*;
+
+    fn install(archive: &Path, game_dir: &Path) -> Result<(), Box<dyn Error>> {
+        let arguments = Arguments {
+            archive: archive.to_path_buf(),
+            game_dir: game_dir.to_path_buf(),
+            target: None,
+        };
+        run(arguments, io::sink())
+    }
+
+    #[test]
+    fn install_test() -> Result<(), Box<dyn Error>> {
+        let temp_dir = TestDir::new("install_test")?;
+        let game_dir = temp_dir.join("game");
+        let archive_path = temp_dir.join("fo2a.bfs");
+        let installed_path = game_dir.join("data").join("FO2A.BFS");
+        fs::create_dir_all(installed_path.parent().unwrap())?;
+        fs::write(&archive_path, b"modded")?;
+        fs::write(&installed_path, b"original")?;
+
+        install(&archive_path, &game_dir)?;
+        assert_eq!(fs::read(&installed_path)?, b"modded");
+
+        fs::write(&archive_path, b"modded again")?;
+        install(&archive_path, &game_dir)?;
+        assert_eq!(fs::read(&installed_path)?, b"modded again");
+
+        assert!(matches!(
+            uninstall_file(&installed_path)?,
+            Some(Uninstalled::Restored(_))
+        ));
+        assert_eq!(fs::read(&installed_path)?, b"original");
+
+        Ok(())
+    }
+
+    #[test]
+    fn install_new_file_test() -> Result<(), Box<dyn Error>> {
+        let temp_dir = TestDir::new("install_new_file_test")?;
+        let game_dir = temp_dir.join("game");
+        let archive_path = temp_dir.join("fo2a.bfs");
+        fs::create_dir_all(&game_dir)?;
+        fs::write(&archive_path, b"modded")?;
+
+        let arguments = Arguments {
+            archive: archive_path,
+            game_dir: game_dir.clone(),
+            target: Some(PathBuf::from("fo2a.bfs")),
+        };
+        run(arguments, io::sink())?;
+        let installed_path = game_dir.join("fo2a.bfs");
+        assert_eq!(fs::read(&installed_path)?, b"modded");
+
+        assert_eq!(uninstall_file(&installed_path)?, Some(Uninstalled::Removed));
+        assert_eq!(fs::read_dir(&game_dir)?.count(), 0);
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn find_files_symlink_loop_test() -> io::Result<()> {
+        let temp_dir = TestDir::new("find_files_symlink_loop_test")?;
+        let data_dir = temp_dir.join("data");
+        fs::create_dir_all(&data_dir)?;
+        fs::write(data_dir.join("FO2A.BFS"), b"original")?;
+        std::os::unix::fs::symlink(&temp_dir.path, data_dir.join("loop"))?;
+
+        assert_eq!(
+            find_files(&temp_dir.path, "fo2a.bfs")?,
+            vec![data_dir.join("FO2A.BFS")]
+        );
+
+        Ok(())
+    }
+}
@@ -2,17 +2,21 @@ use std::error::Error;
 use std::fs;
 use std::path::PathBuf;
 
-use clap::Parser;
+use clap::{Parser, ValueEnum};
+use serde::Serialize;
 use tabled::settings::object::{Columns, Segment};
 use tabled::settings::{Alignment, Modify, Style};
 use tabled::{Table, Tabled};
 
-use bfstool::read_archive_file;
+use bfstool::archive_reader::normalize_separators;
 use bfstool::CompressionMethod;
 
 use crate::display::{display_offset, display_size};
+use crate::encrypted::read_archive_file_with_format_name;
+use crate::file_filter::FileFilter;
+use crate::glob::glob_match;
 
-use super::Format;
+use super::FormatName;
 
 #[derive(Parser)]
 pub struct Arguments {
@@ -21,13 +25,64 @@ pub struct Arguments {
     /// Ignore invalid magic/version/hash size
     #[clap(long)]
     force: bool,
-    /// BFS archive format
+    /// BFS archive format, taken from the config file if not provided. Can also be the name of a
+    /// registered format provider
     #[clap(short, long)]
-    format: Format,
+    format: Option<FormatName>,
+    /// Keys.toml file name, decrypts the archive in memory before reading it
+    #[clap(long)]
+    keys: Option<PathBuf>,
+    /// Sort files by the given column instead of listing them in archive order. Files with equal
+    /// values are always kept in header order, so the output is stable between runs
+    #[clap(long)]
+    order: Option<Order>,
+    /// Sort files in descending order
+    #[clap(long, requires = "order")]
+    descending: bool,
+    /// Only list files with names matching the given pattern, `*` matches any sequence of
+    /// characters and `?` matches any single character
+    #[clap(long)]
+    filter: Option<String>,
+    /// Only list the first N files
+    #[clap(long, conflicts_with = "tail")]
+    head: Option<usize>,
+    /// Only list the last N files
+    #[clap(long)]
+    tail: Option<usize>,
+    #[clap(flatten)]
+    file_filter: FileFilter,
+    /// Output format of the listing
+    #[clap(long, value_enum, default_value_t = Output::Table)]
+    output: Output,
+}
+
+/// Output format of the listing
+#[derive(ValueEnum, Clone, Copy, Eq, PartialEq)]
+pub enum Output {
+    /// Human readable Markdown table
+    Table,
+    /// JSON array of all listed files, without the archive summary
+    Json,
+}
+
+/// Column to sort the listed files by
+#[derive(ValueEnum, Clone, Copy, Eq, PartialEq)]
+pub enum Order {
+    Method,
+    Size,
+    Compressed,
+    Copies,
+    Offset,
+    Name,
+    /// Header index, i.e. archive order
+    Index,
 }
 
 #[derive(Tabled, Eq, PartialEq)]
 pub struct TableFileInfo {
+    #[tabled(skip)]
+    pub index: usize,
+
     #[tabled(rename = "Method")]
     pub method: CompressionMethod,
 
@@ -45,23 +100,62 @@ pub struct TableFileInfo {
 
     #[tabled(rename = "File Name")]
     pub file_name: String,
+
+    #[tabled(skip)]
+    pub hash: Option<u32>,
 }
 
 pub fn run(arguments: Arguments, mut writer: impl std::io::Write) -> Result<(), Box<dyn Error>> {
-    let archive = read_archive_file(&arguments.archive, arguments.format.into(), arguments.force)?;
+    let (_, archive) = read_archive_file_with_format_name(
+        &arguments.archive,
+        arguments.format,
+        arguments.force,
+        arguments.keys.as_deref(),
+    )?;
 
-    let table_contents = archive
+    let filter = arguments.filter.as_deref().map(normalize_separators);
+    let mut table_contents = archive
         .multiple_file_info(archive.file_names())
         .into_iter()
-        .map(|(name, file_info)| TableFileInfo {
+        .enumerate()
+        .filter(|(index, (name, file_info))| {
+            filter
+                .as_ref()
+                .is_none_or(|filter| glob_match(filter, name))
+                && arguments.file_filter.matches(*index, file_info)
+        })
+        .map(|(index, (name, file_info))| TableFileInfo {
+            index,
             method: file_info.compression_method,
             size: file_info.size,
             compressed: file_info.compressed_size,
             copies: file_info.copies,
             offset: file_info.offset,
             file_name: name,
+            hash: file_info.hash,
         })
         .collect::<Vec<TableFileInfo>>();
+    if let Some(order) = arguments.order {
+        sort_files(&mut table_contents, order, arguments.descending);
+    }
+    if let Some(head) = arguments.head {
+        table_contents.truncate(head);
+    }
+    if let Some(tail) = arguments.tail {
+        table_contents.drain(..table_contents.len().saturating_sub(tail));
+    }
+
+    if arguments.output == Output::Json {
+        serde_json::to_writer_pretty(
+            &mut writer,
+            &table_contents
+                .iter()
+                .map(JsonFileInfo::from)
+                .collect::<Vec<_>>(),
+        )?;
+        writeln!(writer)?;
+        return Ok(());
+    }
 
     writeln!(
         writer,
@@ -74,6 +168,9 @@ pub fn run(arguments: Arguments, mut writer: impl std::io::Write) -> Result<(),
         display_size(&fs::metadata(&arguments.archive).unwrap().len())
     )?;
     writeln!(writer, "File count: {}", archive.file_count())?;
+    if table_contents.len() as u64 != archive.file_count() {
+        writeln!(writer, "Listed files: {}", table_contents.len())?;
+    }
     writeln!(
         writer,
         "{}",
@@ -86,6 +183,56 @@ pub fn run(arguments: Arguments, mut writer: impl std::io::Write) -> Result<(),
     Ok(())
 }
 
+/// A listed file in the JSON output
+#[derive(Serialize)]
+pub struct JsonFileInfo<'a> {
+    index: usize,
+    name: &'a str,
+    compression_method: String,
+    size: u64,
+    compressed_size: u64,
+    copies: u64,
+    offset: u64,
+    hash: Option<u32>,
+}
+
+impl<'a> From<&'a TableFileInfo> for JsonFileInfo<'a> {
+    fn from(value: &'a TableFileInfo) -> Self {
+        Self {
+            index: value.index,
+            name: &value.file_name,
+            compression_method: value.method.to_string(),
+            size: value.size,
+            compressed_size: value.compressed,
+            copies: value.copies,
+            offset: value.offset,
+            hash: value.hash,
+        }
+    }
+}
+
+/// Sorts the files by the given column, keeping files with equal values in header order, even
+/// when sorting in descending order
+pub fn sort_files(files: &mut [TableFileInfo], order: Order, descending: bool) {
+    files.sort_by(|a, b| {
+        let ordering = match order {
+            Order::Method => a.method.to_string().cmp(&b.method.to_string()),
+            Order::Size => a.size.cmp(&b.size),
+            Order::Compressed => a.compressed.cmp(&b.compressed),
+            Order::Copies => a.copies.cmp(&b.copies),
+            Order::Offset => a.offset.cmp(&b.offset),
+            Order::Name => a.file_name.cmp(&b.file_name),
+            Order::Index => a.index.cmp(&b.index),
+        };
+        let ordering = if descending {
+            ordering.reverse()
+        } else {
+            ordering
+        };
+        ordering.then(a.index.cmp(&b.index))
+    });
+}
+
 #[cfg(test)]
 mod tests {
     use std::fs::File;
@@ -93,6 +240,8 @@ mod tests {
 
     use pretty_assertions::assert_eq;
 
+    use crate::Format;
+
     use super::*;
 
     #[test]
@@ -101,7 +250,15 @@ mod tests {
         let arguments = Arguments {
             archive: PathBuf::from("test_data/bfs2004a/europe.bin"),
             force: false,
-            format: Format::Bfs2004a,
+            format: Some(FormatName::Builtin(Format::Bfs2004a)),
+            keys: None,
+            order: None,
+            descending: false,
+            filter: None,
+            head: None,
+            tail: None,
+            file_filter: FileFilter::default(),
+            output: Output::Table,
         };
         run(arguments, &mut result)?;
 
@@ -124,4 +281,81 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn sorting_test() {
+        let file = |index, size, offset, file_name: &str| TableFileInfo {
+            index,
+            method: CompressionMethod::None,
+            size,
+            compressed: size,
+            copies: 0,
+            offset,
+            file_name: file_name.to_string(),
+            hash: None,
+        };
+        let mut files = vec![
+            file(0, 0x20, 0x100, "b.txt"),
+            file(1, 0x10, 0x120, "c.txt"),
+            file(2, 0x20, 0x130, "a.txt"),
+        ];
+
+        sort_files(&mut files, Order::Size, true);
+        assert_eq!(
+            files
+                .iter()
+                .map(|file| file.file_name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["b.txt", "a.txt", "c.txt"]
+        );
+
+        sort_files(&mut files, Order::Name, false);
+        assert_eq!(
+            files
+                .iter()
+                .map(|file| file.file_name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["a.txt", "b.txt", "c.txt"]
+        );
+
+        sort_files(&mut files, Order::Index, true);
+        assert_eq!(
+            files
+                .iter()
+                .map(|file| file.file_name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["a.txt", "c.txt", "b.txt"]
+        );
+    }
+
+    #[test]
+    fn json_listing_test() -> Result<(), Box<dyn Error>> {
+        let mut result = Vec::new();
+        let arguments = Arguments {
+            archive: PathBuf::from("test_data/bfs2004a/europe.bin"),
+            force: false,
+            format: Some(FormatName::Builtin(Format::Bfs2004a)),
+            keys: None,
+            order: Some(Order::Name),
+            descending: false,
+            filter: None,
+            head: None,
+            tail: None,
+            file_filter: FileFilter::default(),
+            output: Output::Json,
+        };
+        run(arguments, &mut result)?;
+
+        let files = serde_json::from_slice::<Vec<serde_json::Value>>(&result)?;
+        let names = files
+            .iter()
+            .map(|file| file["name"].as_str().unwrap_or_default().to_string())
+            .collect::<Vec<_>>();
+        let mut sorted_names = names.clone();
+        sorted_names.sort();
+        assert_eq!(names, sorted_names);
+        assert!(files.iter().all(|file| file["index"].is_u64()));
+
+        Ok(())
+    }
 }
@@ -1,13 +1,53 @@
-use std::error::Error;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::str::FromStr;
 
 use clap::{Parser, Subcommand, ValueEnum};
+use serde::Deserialize;
 
+use bfstool::archive_reader::ReadError;
+use bfstool::format_provider::format_provider;
+
+mod bench;
+mod config;
 mod decrypt;
 mod display;
+mod dump_unknown;
 mod encrypt;
+mod encrypted;
+mod exit_code;
+mod export_loose;
 mod extract;
+mod file_filter;
+mod fix_crc;
+mod gaps;
+mod glob;
+mod info;
+mod install;
 mod list;
+mod messages;
+mod names;
+mod probe;
+mod progress;
+mod prompt;
+mod rename;
+mod repair;
+mod research;
+mod restore;
+mod safe_write;
+mod scan_install;
+mod selftest;
+mod serve;
+mod sign;
+#[cfg(test)]
+mod test_archive;
+mod track_pack;
 mod tree;
+mod uninstall;
+mod verify;
+mod verify_signature;
+mod warning;
+mod whois_offset;
 
 #[derive(Parser)]
 #[clap(author, version, about, long_about = None)]
@@ -15,6 +55,25 @@ mod tree;
 struct Cli {
     #[clap(subcommand)]
     command: Commands,
+    /// Treat warnings as errors, exiting with a non-zero exit code if any were emitted
+    #[clap(long, global = true)]
+    strict: bool,
+    /// Name of the config file preset to take default values from
+    #[clap(long, global = true)]
+    preset: Option<String>,
+    /// Do not display progress bars
+    #[clap(long, global = true)]
+    no_progress: bool,
+    /// Encoding of file names in archives, for unofficial archives using a legacy codepage, for
+    /// example windows-1251
+    #[clap(long, global = true)]
+    encoding: Option<String>,
+    /// Message catalog with translated messages, see locales/template.toml
+    #[clap(long, global = true)]
+    messages: Option<PathBuf>,
+    /// Directory to store temporary copies of modified archives in, instead of next to them
+    #[clap(long, global = true)]
+    temp_dir: Option<PathBuf>,
 }
 
 #[derive(Subcommand)]
@@ -31,9 +90,59 @@ enum Commands {
     Decrypt(decrypt::Arguments),
     /// Encrypt an archive
     Encrypt(encrypt::Arguments),
+    /// Detect the format of an archive
+    Probe(probe::Arguments),
+    /// Measure archive opening, extraction and compression performance
+    Bench(bench::Arguments),
+    /// Recalculate and rewrite CRC32 values of all files in the archive
+    FixCrc(fix_crc::Arguments),
+    /// Display a summary of the archive without listing every file
+    Info(info::Arguments),
+    /// Report unreferenced gaps, padding and overlapping file data in the archive
+    Gaps(gaps::Arguments),
+    /// Extract a track folder together with the shared files of its environment
+    TrackPack(track_pack::Arguments),
+    /// Rename a file in the archive by rewriting its file header only
+    Rename(rename::Arguments),
+    /// Serve list and extract requests over JSON-RPC, keeping archives open between requests
+    Serve(serve::Arguments),
+    /// Restore an archive from the latest backup made by a command modifying it
+    Restore(restore::Arguments),
+    /// Check that reading, decryption and compression work correctly on this platform
+    Selftest(selftest::Arguments),
+    /// Create a detached Ed25519 signature of an archive
+    Sign(sign::Arguments),
+    /// Check the detached signature of an archive
+    VerifySignature(verify_signature::Arguments),
+    /// Find all archives in a game installation, optionally extracting all of them
+    ScanInstall(scan_install::Arguments),
+    /// Check the CRC32 values of all files in one or more archives and locate corrupted data
+    Verify(verify::Arguments),
+    /// Fix mechanically recoverable header inconsistencies, such as damaged packed sizes
+    Repair(repair::Arguments),
+    /// List the decoded name table of the archive without reading any file headers
+    Names(names::Arguments),
+    /// Find the archived file whose data contains the given archive offset
+    WhoisOffset(whois_offset::Arguments),
+    /// Export files modified after extraction as loose files for runtime mod loaders, together
+    /// with a manifest of the overridden archive paths
+    ExportLoose(export_loose::Arguments),
+    /// Show the header fields that can be read from a Bfs2011 or Bfs2013 archive and the regions
+    /// nothing is known about yet
+    Research(research::Arguments),
+    /// Export the header bytes of a Bfs2011 or Bfs2013 archive not assigned to any known field as
+    /// annotated hex
+    DumpUnknown(dump_unknown::Arguments),
+    /// Copy an archive into a game installation, backing up the archive it replaces and verifying
+    /// the copy
+    Install(install::Arguments),
+    /// Undo install, restoring the original archive, or removing the installed archive if it did
+    /// not replace one
+    Uninstall(uninstall::Arguments),
 }
 
-#[derive(ValueEnum, Clone, Eq, PartialEq)]
+#[derive(ValueEnum, Deserialize, Clone, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
 enum Format {
     Bfs2004a,
     Bfs2004b,
@@ -42,6 +151,31 @@ enum Format {
     Bzf2002,
 }
 
+/// Format of commands which can also read archives with a registered format provider, either a
+/// built-in format or the name of the provider
+#[derive(Clone, Eq, PartialEq)]
+enum FormatName {
+    Builtin(Format),
+    Provider(String),
+}
+
+impl FromStr for FormatName {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        if let Ok(format) = <Format as ValueEnum>::from_str(name, true) {
+            Ok(Self::Builtin(format))
+        } else if format_provider(name).is_some() {
+            Ok(Self::Provider(name.to_string()))
+        } else {
+            Err(format!(
+                "{} is neither a supported format nor the name of a registered format provider",
+                name
+            ))
+        }
+    }
+}
+
 #[derive(ValueEnum, Clone, Eq, PartialEq)]
 enum CryptFormat {
     Bzf2001,
@@ -59,13 +193,74 @@ impl From<Format> for bfstool::Format {
     }
 }
 
-fn main() -> Result<(), Box<dyn Error>> {
+fn main() -> ExitCode {
     let cli: Cli = Cli::parse();
-    match cli.command {
-        Commands::List(arguments) => list::run(arguments, &mut std::io::stdout()),
-        Commands::Tree(arguments) => tree::run(arguments, &mut std::io::stdout()),
-        Commands::Extract(arguments) => extract::run(arguments),
-        Commands::Decrypt(arguments) => decrypt::run(arguments),
-        Commands::Encrypt(arguments) => encrypt::run(arguments),
+    if cli.no_progress {
+        progress::hide_progress();
+    }
+    let result = messages::load(cli.messages.as_deref())
+        .and_then(|_| config::load(cli.preset.as_deref(), cli.encoding, cli.temp_dir))
+        .and_then(|_| match cli.command {
+            Commands::List(arguments) => list::run(arguments, &mut std::io::stdout()),
+            Commands::Tree(arguments) => tree::run(arguments, &mut std::io::stdout()),
+            Commands::Extract(arguments) => extract::run(arguments),
+            Commands::Decrypt(arguments) => decrypt::run(arguments),
+            Commands::Encrypt(arguments) => encrypt::run(arguments),
+            Commands::Probe(arguments) => probe::run(arguments, &mut std::io::stdout()),
+            Commands::Bench(arguments) => bench::run(arguments, &mut std::io::stdout()),
+            Commands::FixCrc(arguments) => fix_crc::run(arguments, &mut std::io::stdout()),
+            Commands::Info(arguments) => info::run(arguments, &mut std::io::stdout()),
+            Commands::Gaps(arguments) => gaps::run(arguments, &mut std::io::stdout()),
+            Commands::TrackPack(arguments) => track_pack::run(arguments, &mut std::io::stdout()),
+            Commands::Rename(arguments) => rename::run(arguments, &mut std::io::stdout()),
+            Commands::Serve(arguments) => serve::run(arguments, &mut std::io::stdout()),
+            Commands::Restore(arguments) => restore::run(arguments, &mut std::io::stdout()),
+            Commands::Selftest(arguments) => selftest::run(arguments, &mut std::io::stdout()),
+            Commands::Sign(arguments) => sign::run(arguments, &mut std::io::stdout()),
+            Commands::VerifySignature(arguments) => {
+                verify_signature::run(arguments, &mut std::io::stdout())
+            }
+            Commands::ScanInstall(arguments) => {
+                scan_install::run(arguments, &mut std::io::stdout())
+            }
+            Commands::Verify(arguments) => verify::run(arguments, &mut std::io::stdout()),
+            Commands::Repair(arguments) => repair::run(arguments, &mut std::io::stdout()),
+            Commands::Names(arguments) => names::run(arguments, &mut std::io::stdout()),
+            Commands::WhoisOffset(arguments) => {
+                whois_offset::run(arguments, &mut std::io::stdout())
+            }
+            Commands::ExportLoose(arguments) => {
+                export_loose::run(arguments, &mut std::io::stdout())
+            }
+            Commands::Research(arguments) => research::run(arguments, &mut std::io::stdout()),
+            Commands::DumpUnknown(arguments) => {
+                dump_unknown::run(arguments, &mut std::io::stdout())
+            }
+            Commands::Install(arguments) => install::run(arguments, &mut std::io::stdout()),
+            Commands::Uninstall(arguments) => uninstall::run(arguments, &mut std::io::stdout()),
+        });
+    let result = result.and_then(|_| {
+        if cli.strict && warning::warning_count() > 0 {
+            Err(messages::message("error.strict", &[&warning::warning_count()]).into())
+        } else {
+            Ok(())
+        }
+    });
+    if let Err(error) = &result {
+        eprintln!("{}", messages::message("error", &[error]));
+        match error.downcast_ref::<ReadError>() {
+            Some(ReadError::LikelyEncrypted { format }) => {
+                eprintln!("{}", messages::message("error.encrypted", &[format]));
+            }
+            Some(ReadError::FormatMismatch { detected, .. }) => {
+                eprintln!(
+                    "{}",
+                    messages::message("error.format_mismatch", &[detected])
+                );
+            }
+            _ => {}
+        }
+        return ExitCode::from(exit_code::exit_code(error.as_ref()));
     }
+    ExitCode::SUCCESS
 }
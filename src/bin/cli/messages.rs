@@ -0,0 +1,83 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::Display;
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// English messages, used for messages missing from the loaded catalog
+const ENGLISH: &str = include_str!("../../../locales/en.toml");
+
+static ENGLISH_CATALOG: OnceLock<HashMap<String, String>> = OnceLock::new();
+
+static CATALOG: OnceLock<HashMap<String, String>> = OnceLock::new();
+
+/// Loads the message catalog from the given file, see `locales/template.toml`
+///
+/// Without a file, or for messages the file leaves empty, English messages are used
+pub fn load(path: Option<&Path>) -> Result<(), Box<dyn Error>> {
+    let catalog = match path {
+        Some(path) => toml::from_str::<HashMap<String, String>>(&fs::read_to_string(path)?)?
+            .into_iter()
+            .filter(|(_, message)| !message.is_empty())
+            .collect(),
+        None => HashMap::new(),
+    };
+    let _ = CATALOG.set(catalog);
+    Ok(())
+}
+
+/// Returns the message with the given key, with every `{}` replaced by the next argument
+pub fn message(key: &str, arguments: &[&dyn Display]) -> String {
+    let english = ENGLISH_CATALOG.get_or_init(|| {
+        toml::from_str(ENGLISH).expect("English message catalog is not valid TOML")
+    });
+    let template = CATALOG
+        .get()
+        .and_then(|catalog| catalog.get(key))
+        .or_else(|| english.get(key))
+        .map_or(key, String::as_str);
+
+    let mut arguments = arguments.iter();
+    let mut parts = template.split("{}");
+    let mut result = parts.next().unwrap_or_default().to_string();
+    for part in parts {
+        if let Some(argument) = arguments.next() {
+            result.push_str(&argument.to_string());
+        }
+        result.push_str(part);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn message_test() {
+        assert_eq!(message("warning", &[&"test"]), "Warning: test");
+        assert_eq!(
+            message("extract.extracted_files", &[&16]),
+            "Extracted 16 files."
+        );
+        assert_eq!(message("missing.key", &[]), "missing.key");
+    }
+
+    #[test]
+    fn template_test() {
+        let english = toml::from_str::<HashMap<String, String>>(ENGLISH).unwrap();
+        let template = toml::from_str::<HashMap<String, String>>(include_str!(
+            "../../../locales/template.toml"
+        ))
+        .unwrap();
+
+        let mut english_keys = english.keys().collect::<Vec<_>>();
+        english_keys.sort();
+        let mut template_keys = template.keys().collect::<Vec<_>>();
+        template_keys.sort();
+        assert_eq!(english_keys, template_keys);
+    }
+}
@@ -0,0 +1,169 @@
+use std::error::Error;
+use std::io::{BufReader, Seek, Write};
+use std::path::PathBuf;
+
+use binrw::BinRead;
+use clap::Parser;
+use tabled::settings::object::{Columns, Segment};
+use tabled::settings::{Alignment, Modify, Style};
+use tabled::{Table, Tabled};
+
+use bfstool::archive_reader::open_archive_file;
+use bfstool::formats::{bfs2004b, bfs2007};
+
+use crate::config;
+use crate::display::display_offset;
+use crate::warning::check_forced_format;
+
+use super::Format;
+
+#[derive(Parser)]
+pub struct Arguments {
+    /// BFS archive file name
+    archive: PathBuf,
+    /// Ignore invalid magic/version/hash size
+    #[clap(long)]
+    force: bool,
+    /// BFS archive format, taken from the config file if not provided
+    #[clap(short, long)]
+    format: Option<Format>,
+    /// Also read the file headers and check that the names they reference exist, that every name
+    /// is referenced and that all names decode to their stored length
+    #[clap(long)]
+    check: bool,
+}
+
+#[derive(Tabled)]
+struct TableName {
+    #[tabled(rename = "ID")]
+    id: usize,
+
+    #[tabled(rename = "Offset", display_with = "display_offset")]
+    offset: u64,
+
+    #[tabled(rename = "Length")]
+    length: u16,
+
+    #[tabled(rename = "Name")]
+    name: String,
+}
+
+pub fn run(arguments: Arguments, mut writer: impl Write) -> Result<(), Box<dyn Error>> {
+    let format = config::format(arguments.format, &arguments.archive)?;
+
+    if arguments.force {
+        check_forced_format(&arguments.archive, format)?;
+    }
+
+    let mut reader = BufReader::new(open_archive_file(&arguments.archive)?);
+    match format {
+        bfstool::Format::Bfs2004b if !arguments.force => bfs2004b::check_archive(&mut reader)?,
+        bfstool::Format::Bfs2007 if !arguments.force => bfs2007::check_archive(&mut reader)?,
+        bfstool::Format::Bfs2004b | bfstool::Format::Bfs2007 => {}
+        _ => {
+            return Err(format!("{} archives do not have a name table", format).into());
+        }
+    }
+    reader.rewind()?;
+    let name_table = bfs2004b::NameTable::read(&mut reader)?;
+
+    let table_contents = name_table
+        .decode_names(config::name_encoding())
+        .into_iter()
+        .zip(&name_table.file_name_offset_table)
+        .zip(&name_table.file_name_length_table)
+        .enumerate()
+        .map(|(id, ((name, offset), length))| TableName {
+            id,
+            offset: *offset as u64,
+            length: *length,
+            name,
+        })
+        .collect::<Vec<TableName>>();
+
+    let problems = if arguments.check {
+        reader.rewind()?;
+        let referenced_ids = if format == bfstool::Format::Bfs2004b {
+            bfs2004b::RawArchive::read(&mut reader)?
+                .file_headers
+                .iter()
+                .enumerate()
+                .map(|(index, file_header)| (index, file_header.folder_id, file_header.file_id))
+                .collect::<Vec<_>>()
+        } else {
+            bfs2007::RawArchive::read(&mut reader)?
+                .file_headers
+                .iter()
+                .enumerate()
+                .map(|(index, file_header)| (index, file_header.folder_id, file_header.file_id))
+                .collect::<Vec<_>>()
+        };
+        Some(name_table.check(referenced_ids))
+    } else {
+        None
+    };
+
+    writeln!(
+        writer,
+        "Listing names of archive: {}",
+        arguments.archive.to_string_lossy()
+    )?;
+    writeln!(writer, "Name count: {}", table_contents.len())?;
+    if let Some(problems) = &problems {
+        writeln!(writer, "Name table problems: {}", problems.len())?;
+        for problem in problems {
+            writeln!(writer, "{}", problem)?;
+        }
+    }
+    writeln!(
+        writer,
+        "{}",
+        Table::new(table_contents)
+            .with(Style::markdown())
+            .with(Modify::new(Segment::all()).with(Alignment::right()))
+            .with(Modify::new(Columns::single(1)).with(Alignment::center()))
+            .with(Modify::new(Columns::last()).with(Alignment::left()))
+    )?;
+
+    match problems {
+        Some(problems) if !problems.is_empty() => {
+            Err(format!("{} name table problem(s) found", problems.len()).into())
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn names_test() -> Result<(), Box<dyn Error>> {
+        let mut result = Vec::new();
+        let arguments = Arguments {
+            archive: PathBuf::from("test_data/bfs2004b/fo2a.bin"),
+            force: false,
+            format: Some(Format::Bfs2004b),
+            check: true,
+        };
+        run(arguments, &mut result)?;
+
+        let result = String::from_utf8_lossy(&result).to_string();
+        assert!(result.contains("Name table problems: 0"));
+        // Skip the archive name, name count, problem count and table header lines
+        let first_name = result
+            .lines()
+            .nth(5)
+            .unwrap()
+            .split('|')
+            .map(str::trim)
+            .collect::<Vec<&str>>();
+        assert_eq!(first_name[1], "0");
+        assert_eq!(first_name[3], "6");
+        assert_eq!(first_name[4], "01.ogg");
+
+        Ok(())
+    }
+}
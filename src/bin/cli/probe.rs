@@ -0,0 +1,176 @@
+use std::error::Error;
+use std::io::{BufRead, BufReader, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+
+use binrw::BinRead;
+use clap::Parser;
+
+use bfstool::archive_reader::{find_nested_archives, open_archive_file, probe_encrypted};
+use bfstool::format_provider::probe_format_providers;
+use bfstool::formats::{bfs2004a, bzf2001, bzf2002, format_version};
+use bfstool::{read_archive_file_with_options, Format};
+
+use crate::config;
+use crate::display::display_offset;
+use crate::warning::warn_archive;
+
+#[derive(Parser)]
+pub struct Arguments {
+    /// Archive file name
+    archive: PathBuf,
+    /// Also look for archives stored inside the archive, this requires decompressing all files
+    #[clap(long)]
+    nested: bool,
+}
+
+pub fn run(arguments: Arguments, mut writer: impl Write) -> Result<(), Box<dyn Error>> {
+    let file = open_archive_file(&arguments.archive)?;
+    let mut reader = BufReader::new(file);
+
+    writeln!(
+        writer,
+        "Probing archive: {}",
+        arguments.archive.to_string_lossy()
+    )?;
+
+    let Some(format) = bfstool::probe(&mut reader) else {
+        match probe_format_providers(&mut reader) {
+            Some(provider) => writeln!(writer, "Format: {} (external)", provider.name())?,
+            None => writeln!(writer, "Format: unknown")?,
+        }
+        return Ok(());
+    };
+
+    writeln!(writer, "Format: {}", format)?;
+    write_header_fields(&mut writer, &mut reader, format)?;
+    if probe_encrypted(&mut reader, format) {
+        writeln!(
+            writer,
+            "Encrypted: likely, the archive has to be decrypted first"
+        )?;
+        return Ok(());
+    }
+
+    if arguments.nested {
+        let mut archive = read_archive_file_with_options(
+            &arguments.archive,
+            format,
+            config::read_options(false),
+        )?;
+        warn_archive(&arguments.archive, archive.as_ref());
+        let nested_archives = find_nested_archives(archive.as_mut())?;
+        writeln!(writer, "Nested archives: {}", nested_archives.len())?;
+        for (file_name, _, nested_format) in nested_archives {
+            writeln!(writer, "{} ({})", file_name, nested_format)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes the magic, version and other fields of the archive header of an archive of the given
+/// format
+pub fn write_header_fields(
+    writer: &mut impl Write,
+    reader: &mut (impl BufRead + Seek),
+    format: Format,
+) -> Result<(), Box<dyn Error>> {
+    reader.seek(SeekFrom::Start(0))?;
+    match format {
+        Format::Bzf2001 => {
+            let archive_header = bzf2001::ArchiveHeader::read(reader)?;
+            writeln!(
+                writer,
+                "Magic: {}",
+                String::from_utf8_lossy(&archive_header.magic.to_le_bytes())
+            )?;
+            // Bzf2001 stores the year in the lower half of the version
+            writeln!(
+                writer,
+                "Version: {}",
+                format_version(archive_header.version.rotate_left(16))
+            )?;
+            writeln!(writer, "File count: {}", archive_header.file_count)?;
+        }
+        Format::Bzf2002 => {
+            let archive_header = bzf2002::ArchiveHeader::read(reader)?;
+            writeln!(
+                writer,
+                "Magic: {}",
+                String::from_utf8_lossy(&archive_header.magic.to_le_bytes())
+            )?;
+            writeln!(
+                writer,
+                "Version: {}",
+                format_version(archive_header.version)
+            )?;
+            writeln!(
+                writer,
+                "Header size: {}",
+                display_offset(&(archive_header.header_size as u64))
+            )?;
+            writeln!(writer, "File count: {}", archive_header.file_count)?;
+        }
+        Format::Bfs2004a | Format::Bfs2004b | Format::Bfs2007 => {
+            let archive_header = bfs2004a::ArchiveHeader::read(reader)?;
+            writeln!(
+                writer,
+                "Magic: {}",
+                String::from_utf8_lossy(&archive_header.magic.to_le_bytes())
+            )?;
+            writeln!(
+                writer,
+                "Version: {}",
+                format_version(archive_header.version)
+            )?;
+            writeln!(
+                writer,
+                "Header end: {}",
+                display_offset(&(archive_header.header_end as u64))
+            )?;
+            writeln!(writer, "File count: {}", archive_header.file_count)?;
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+    use std::io::Read;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn probing_test() -> Result<(), Box<dyn Error>> {
+        let mut result = Vec::new();
+        let arguments = Arguments {
+            archive: PathBuf::from("test_data/bfs2004a/europe.bin"),
+            nested: false,
+        };
+        run(arguments, &mut result)?;
+
+        let mut expected_result_file = File::open("test_data/cli/probe.txt")?;
+        let mut expected_result = Vec::new();
+        expected_result_file.read_to_end(&mut expected_result)?;
+
+        // Compare results as strings for pretty diff when mismatching
+        //
+        // Ignore mismatching line breaks when comparing (assume \r\n and \n are equal) by
+        // removing all occurrences of \r
+        assert_eq!(
+            String::from_utf8_lossy(&result)
+                .to_string()
+                .replace('\r', ""),
+            String::from_utf8_lossy(&expected_result)
+                .to_string()
+                .replace('\r', "")
+        );
+
+        Ok(())
+    }
+}
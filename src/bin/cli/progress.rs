@@ -0,0 +1,45 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use indicatif::{ProgressBar, ProgressStyle};
+
+static PROGRESS_HIDDEN: AtomicBool = AtomicBool::new(false);
+
+/// Hides all progress bars created afterwards
+pub fn hide_progress() {
+    PROGRESS_HIDDEN.store(true, Ordering::Relaxed);
+}
+
+fn new_bar(length: u64, template: &str) -> ProgressBar {
+    let bar = if PROGRESS_HIDDEN.load(Ordering::Relaxed) {
+        ProgressBar::hidden()
+    } else {
+        ProgressBar::new(length)
+    };
+    bar.set_length(length);
+    bar.set_style(
+        ProgressStyle::default_bar()
+            .template(template)
+            .unwrap()
+            .progress_chars("##-"),
+    );
+    bar
+}
+
+/// Creates a progress bar counting files
+pub fn file_bar(file_count: u64) -> ProgressBar {
+    new_bar(file_count, "[{elapsed}] {wide_bar} [{pos}/{len}]")
+}
+
+/// Creates a progress bar counting bytes
+pub fn byte_bar(size: u64) -> ProgressBar {
+    new_bar(size, "[{elapsed}] {wide_bar} [{bytes}/{total_bytes}]")
+}
+
+/// Prints a line above the progress bar, or directly if the progress bar is hidden
+pub fn println(bar: &ProgressBar, message: impl AsRef<str>) {
+    if bar.is_hidden() {
+        println!("{}", message.as_ref());
+    } else {
+        bar.println(message);
+    }
+}
@@ -0,0 +1,62 @@
+use std::io;
+use std::io::{BufRead, IsTerminal, Write};
+
+use crate::messages::message;
+
+/// Returns true if missing arguments can be asked for, i.e. both stdin and stderr are terminals
+pub fn is_interactive() -> bool {
+    io::stdin().is_terminal() && io::stderr().is_terminal()
+}
+
+/// Asks the user to pick one of the given options by number
+///
+/// Picks the suggested option, if any, when the answer is empty. Asks again on invalid answers.
+pub fn pick<T: AsRef<str>>(
+    question: &str,
+    options: &[T],
+    suggestion: Option<usize>,
+) -> io::Result<usize> {
+    let mut stderr = io::stderr();
+    writeln!(stderr, "{}", question)?;
+    for (index, option) in options.iter().enumerate() {
+        writeln!(
+            stderr,
+            "{:>3}) {}{}",
+            index + 1,
+            option.as_ref(),
+            if suggestion == Some(index) {
+                format!(" ({})", message("prompt.detected", &[]))
+            } else {
+                String::new()
+            }
+        )?;
+    }
+    loop {
+        let answer = ask(&message("prompt.number", &[]))?;
+        if answer.is_empty() {
+            if let Some(suggestion) = suggestion {
+                return Ok(suggestion);
+            }
+        }
+        match answer.parse::<usize>() {
+            Ok(number) if (1..=options.len()).contains(&number) => return Ok(number - 1),
+            _ => writeln!(stderr, "{}", message("prompt.invalid", &[&answer]))?,
+        }
+    }
+}
+
+/// Asks the user a question, returning the answer without surrounding whitespace
+pub fn ask(question: &str) -> io::Result<String> {
+    let mut stderr = io::stderr();
+    write!(stderr, "{} ", question)?;
+    stderr.flush()?;
+
+    let mut answer = String::new();
+    if io::stdin().lock().read_line(&mut answer)? == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "No answer given",
+        ));
+    }
+    Ok(answer.trim().to_string())
+}
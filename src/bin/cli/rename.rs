@@ -0,0 +1,148 @@
+use std::error::Error;
+use std::io::{BufReader, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+
+use clap::Parser;
+
+use bfstool::archive_reader::{
+    normalize_separators, open_archive_file, probe_encrypted, ReadError,
+};
+use bfstool::read_archive_file_with_options;
+
+use crate::config;
+use crate::exit_code::NotFound;
+use crate::safe_write::modify_file;
+use crate::warning::{check_forced_format, warn_archive};
+
+use super::Format;
+
+#[derive(Parser)]
+pub struct Arguments {
+    /// BFS archive file name
+    archive: PathBuf,
+    /// Ignore invalid magic/version/hash size
+    #[clap(long)]
+    force: bool,
+    /// BFS archive format, taken from the config file if not provided
+    #[clap(short, long)]
+    format: Option<Format>,
+    /// Current name of the file in the archive
+    file_name: String,
+    /// New name of the file in the archive
+    new_file_name: String,
+    /// Keep the original archive as a backup, which can be restored with the restore command
+    #[clap(long)]
+    backup: bool,
+}
+
+pub fn run(arguments: Arguments, mut writer: impl Write) -> Result<(), Box<dyn Error>> {
+    let arguments = Arguments {
+        file_name: normalize_separators(&arguments.file_name),
+        new_file_name: normalize_separators(&arguments.new_file_name),
+        ..arguments
+    };
+    let format = config::format(arguments.format, &arguments.archive)?;
+
+    if arguments.force {
+        check_forced_format(&arguments.archive, format)?;
+        // The plain name would be written into the encrypted file headers
+        let mut reader = BufReader::new(open_archive_file(&arguments.archive)?);
+        if probe_encrypted(&mut reader, format) {
+            return Err(ReadError::LikelyEncrypted { format }.into());
+        }
+    }
+
+    let archive = read_archive_file_with_options(
+        &arguments.archive,
+        format,
+        config::read_options(arguments.force),
+    )?;
+    warn_archive(&arguments.archive, archive.as_ref());
+
+    let name_fields = archive.name_field_offsets();
+    if name_fields.is_empty() {
+        return Err(format!("Renaming files is not supported for {} archives", format).into());
+    }
+    if name_fields
+        .iter()
+        .any(|(file_name, _, _)| *file_name == arguments.new_file_name)
+    {
+        return Err(format!("{} already exists in the archive", arguments.new_file_name).into());
+    }
+    let Some((_, name_field_offset, name_field_size)) = name_fields
+        .into_iter()
+        .find(|(file_name, _, _)| *file_name == arguments.file_name)
+    else {
+        return Err(NotFound(arguments.file_name).into());
+    };
+    let name_encoding = config::name_encoding();
+    let Some(mut name_field) = name_encoding.encode(&arguments.new_file_name) else {
+        return Err(format!(
+            "{} can not be represented in the {} file name encoding",
+            arguments.new_file_name,
+            name_encoding.name()
+        )
+        .into());
+    };
+    if name_field.len() as u64 > name_field_size {
+        return Err(format!(
+            "{} is too long, file names can be at most {} bytes long",
+            arguments.new_file_name, name_field_size
+        )
+        .into());
+    }
+    drop(archive);
+
+    name_field.resize(name_field_size as usize, 0);
+
+    modify_file(&arguments.archive, arguments.backup, |file| {
+        file.seek(SeekFrom::Start(name_field_offset))?;
+        file.write_all(&name_field)
+    })?;
+
+    writeln!(
+        writer,
+        "Renamed {} to {}",
+        arguments.file_name, arguments.new_file_name
+    )?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::io;
+
+    use pretty_assertions::assert_eq;
+
+    use bfstool::read_archive_file;
+
+    use crate::test_archive::TestDir;
+
+    use super::*;
+
+    #[test]
+    fn rename_test() -> Result<(), Box<dyn Error>> {
+        let temp_dir = TestDir::new("rename_test")?;
+        let archive_path = temp_dir.join("language.bin");
+        fs::copy("test_data/bzf2001/language.bin", &archive_path)?;
+
+        let arguments = Arguments {
+            archive: archive_path.clone(),
+            force: false,
+            format: Some(Format::Bzf2001),
+            file_name: "credits.txt".to_string(),
+            new_file_name: "data/credits.txt".to_string(),
+            backup: false,
+        };
+        run(arguments, io::sink())?;
+
+        let archive = read_archive_file(&archive_path, bfstool::Format::Bzf2001, false)?;
+        let file_names = archive.file_names();
+
+        assert_eq!(file_names[0], "data/credits.txt");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,388 @@
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+
+use binrw::BinRead;
+use clap::Parser;
+use tabled::settings::object::{Columns, Segment};
+use tabled::settings::{Alignment, Modify, Style};
+use tabled::{Table, Tabled};
+
+use bfstool::compression::zlib_stream_size;
+use bfstool::formats::{bfs2004a, bfs2004b, bfs2007};
+use bfstool::{read_archive_file_with_options, CompressionMethod};
+
+use crate::config;
+use crate::display::display_offset;
+use crate::progress::file_bar;
+use crate::safe_write::modify_file;
+use crate::warning::{check_forced_format, warn, warn_archive};
+
+use super::Format;
+
+#[derive(Parser)]
+pub struct Arguments {
+    /// BFS archive file name
+    archive: PathBuf,
+    /// Ignore invalid magic/version/hash size
+    #[clap(long)]
+    force: bool,
+    /// BFS archive format, taken from the config file if not provided
+    #[clap(short, long)]
+    format: Option<Format>,
+    /// Only report the fields that would change without writing them
+    #[clap(long)]
+    dry_run: bool,
+    /// Keep the original archive as a backup, which can be restored with the restore command
+    #[clap(long)]
+    backup: bool,
+}
+
+#[derive(Tabled)]
+struct Repair {
+    #[tabled(rename = "Field")]
+    field: &'static str,
+
+    #[tabled(rename = "Old", display_with = "display_offset")]
+    old: u64,
+
+    #[tabled(rename = "New", display_with = "display_offset")]
+    new: u64,
+
+    #[tabled(rename = "Field Offset", display_with = "display_offset")]
+    field_offset: u64,
+
+    #[tabled(rename = "File Name")]
+    file_name: String,
+
+    /// Size of the field in bytes
+    #[tabled(skip)]
+    size: usize,
+}
+
+pub fn run(arguments: Arguments, mut writer: impl Write) -> Result<(), Box<dyn Error>> {
+    let format = config::format(arguments.format, &arguments.archive)?;
+
+    if arguments.force {
+        check_forced_format(&arguments.archive, format)?;
+    }
+
+    let mut reader = BufReader::new(File::open(&arguments.archive)?);
+    let mut repairs = match format {
+        bfstool::Format::Bfs2004a => bfs2004a_layout_repairs(&mut reader)?,
+        bfstool::Format::Bfs2004b => {
+            // The header section ends right after the last file header
+            let raw_archive = bfs2004b::RawArchive::read(&mut reader)?;
+            header_end_repair(
+                raw_archive.archive_header.header_end,
+                reader.stream_position()?,
+            )
+            .into_iter()
+            .collect()
+        }
+        bfstool::Format::Bfs2007 => {
+            let raw_archive = bfs2007::RawArchive::read(&mut reader)?;
+            header_end_repair(
+                raw_archive.archive_header.header_end,
+                reader.stream_position()?,
+            )
+            .into_iter()
+            .collect()
+        }
+        _ => Vec::new(),
+    };
+    drop(reader);
+
+    // A damaged header can prevent the archive from being read, its packed sizes can only be
+    // checked once the header is repaired
+    let archive = match read_archive_file_with_options(
+        &arguments.archive,
+        format,
+        config::read_options(arguments.force),
+    ) {
+        Ok(archive) => Some(archive),
+        Err(error) if !repairs.is_empty() => {
+            warn(format!(
+                "Packed sizes can not be checked before the header is repaired, run repair again \
+                 afterwards: {}",
+                error
+            ));
+            None
+        }
+        Err(error) => return Err(error.into()),
+    };
+    if let Some(mut archive) = archive {
+        warn_archive(&arguments.archive, archive.as_ref());
+        let size_fields = archive.size_field_offsets();
+        let bar = file_bar(size_fields.len() as u64);
+        for (file_name, size_field_offset, file_info) in size_fields {
+            bar.inc(1);
+            if file_info.compression_method != CompressionMethod::Zlib {
+                continue;
+            }
+            let reader = archive.reader();
+            reader.seek(SeekFrom::Start(file_info.offset))?;
+            match zlib_stream_size(reader) {
+                Ok((packed_size, _)) if packed_size != file_info.compressed_size => {
+                    repairs.push(Repair {
+                        field: "packed size",
+                        old: file_info.compressed_size,
+                        new: packed_size,
+                        field_offset: size_field_offset,
+                        file_name,
+                        size: 4,
+                    });
+                }
+                Ok(_) => {}
+                Err(error) => warn(format!(
+                    "{} can not be decompressed and will not be repaired: {}",
+                    file_name, error
+                )),
+            }
+        }
+        bar.finish_and_clear();
+    }
+
+    if !arguments.dry_run && !repairs.is_empty() {
+        modify_file(&arguments.archive, arguments.backup, |file| {
+            for repair in &repairs {
+                file.seek(SeekFrom::Start(repair.field_offset))?;
+                file.write_all(&repair.new.to_le_bytes()[..repair.size])?;
+            }
+            Ok(())
+        })?;
+    }
+
+    writeln!(
+        writer,
+        "{} archive: {}",
+        if arguments.dry_run {
+            "Checking"
+        } else {
+            "Repairing"
+        },
+        arguments.archive.to_string_lossy()
+    )?;
+    writeln!(writer, "Repaired fields: {}", repairs.len())?;
+    if !repairs.is_empty() {
+        writeln!(
+            writer,
+            "{}",
+            Table::new(repairs)
+                .with(Style::markdown())
+                .with(Modify::new(Segment::all()).with(Alignment::right()))
+                .with(Modify::new(Columns::first()).with(Alignment::left()))
+                .with(Modify::new(Columns::single(3)).with(Alignment::center()))
+                .with(Modify::new(Columns::last()).with(Alignment::left()))
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Size of a Bfs2004a file header without the file name and the copies offsets
+const BFS2004A_FILE_HEADER_SIZE: u64 = 0x16;
+
+/// Finds the copy counts and the header end of a Bfs2004a archive not matching its layout
+///
+/// File headers are located through the file header offset table, so the copy count of every file
+/// follows from where the next file header starts. Only the copy count of the file header stored
+/// last is taken as is, which then determines where the header section ends.
+///
+/// Bfs2004b and Bfs2007 archives store their file headers back to back without such a table, so a
+/// wrong copy count can not be attributed to a single file header and is not repaired.
+fn bfs2004a_layout_repairs(reader: &mut (impl Read + Seek)) -> Result<Vec<Repair>, Box<dyn Error>> {
+    let archive_header = bfs2004a::ArchiveHeader::read(reader)?;
+    let mut file_header_offsets = (0..archive_header.file_count)
+        .map(|_| u32::read_le(reader).map(u64::from))
+        .collect::<Result<Vec<u64>, _>>()?;
+    bfs2004a::HashTable::read(reader)?;
+    file_header_offsets.sort();
+    file_header_offsets.dedup();
+
+    let mut repairs = Vec::new();
+    let mut header_end = reader.stream_position()?;
+    for (index, &file_header_offset) in file_header_offsets.iter().enumerate() {
+        reader.seek(SeekFrom::Start(file_header_offset))?;
+        let mut file_header = [0; BFS2004A_FILE_HEADER_SIZE as usize];
+        reader.read_exact(&mut file_header)?;
+        let copies = file_header[0x1] as u64;
+        let file_name_length = u16::from_le_bytes([file_header[0x14], file_header[0x15]]) as u64;
+        let mut file_name = vec![0; file_name_length as usize];
+        reader.read_exact(&mut file_name)?;
+        let file_name = config::name_encoding().decode(&file_name);
+
+        let copies_offsets_start =
+            file_header_offset + BFS2004A_FILE_HEADER_SIZE + file_name_length;
+        let actual_copies = match file_header_offsets.get(index + 1) {
+            Some(&next_offset) => match next_offset.checked_sub(copies_offsets_start) {
+                Some(space) if space % 4 == 0 && space / 4 <= u8::MAX as u64 => space / 4,
+                _ => {
+                    warn(format!(
+                        "{} does not end where the next file header starts and will not be repaired",
+                        file_name
+                    ));
+                    copies
+                }
+            },
+            None => copies,
+        };
+        if actual_copies != copies {
+            repairs.push(Repair {
+                field: "copy count",
+                old: copies,
+                new: actual_copies,
+                field_offset: file_header_offset + 0x1,
+                file_name,
+                size: 1,
+            });
+        }
+        header_end = copies_offsets_start + actual_copies * 4;
+    }
+
+    repairs.extend(header_end_repair(archive_header.header_end, header_end));
+
+    Ok(repairs)
+}
+
+/// Returns the repair of the header end field if it differs from where the header section ends
+fn header_end_repair(stored: u32, header_end: u64) -> Option<Repair> {
+    (stored as u64 != header_end).then(|| Repair {
+        field: "header end",
+        old: stored as u64,
+        new: header_end,
+        field_offset: 0x8,
+        file_name: String::new(),
+        size: 4,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::io;
+
+    use pretty_assertions::assert_eq;
+
+    use bfstool::compression::compress;
+
+    use crate::test_archive::TestDir;
+
+    use super::*;
+
+    #[test]
+    fn repair_test() -> Result<(), Box<dyn Error>> {
+        let data = b"data/language/version.ini ".repeat(64);
+        let mut compressed = Vec::new();
+        compress(
+            &mut data.as_slice(),
+            &mut compressed,
+            CompressionMethod::Zlib,
+            None,
+        )?;
+        let file_name = b"version.ini";
+        let data_offset = 0x10 + 0x13 + file_name.len() as u32;
+
+        // Made up Bzf2002 archive storing a zlib compressed file with a damaged packed size
+        let mut archive = Vec::new();
+        archive.extend_from_slice(b"bzf2");
+        archive.extend_from_slice(&0x20021011u32.to_le_bytes());
+        archive.extend_from_slice(&data_offset.to_le_bytes());
+        archive.extend_from_slice(&1u32.to_le_bytes());
+        archive.push(0x01);
+        archive.extend_from_slice(&data_offset.to_le_bytes());
+        archive.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        archive.extend_from_slice(&0x10u32.to_le_bytes());
+        archive.extend_from_slice(&0u32.to_le_bytes());
+        archive.extend_from_slice(&(file_name.len() as u16).to_le_bytes());
+        archive.extend_from_slice(file_name);
+        archive.extend_from_slice(&compressed);
+        let temp_dir = TestDir::new("repair_test")?;
+        let archive_path = temp_dir.join("archive.bzf");
+        fs::write(&archive_path, &archive)?;
+
+        let arguments = Arguments {
+            archive: archive_path.clone(),
+            force: false,
+            format: Some(Format::Bzf2002),
+            dry_run: false,
+            backup: false,
+        };
+        run(arguments, io::sink())?;
+
+        let repaired = fs::read(&archive_path)?;
+
+        assert_eq!(
+            &repaired[0x19..0x1D],
+            &(compressed.len() as u32).to_le_bytes()
+        );
+        assert_eq!(&repaired[..0x19], &archive[..0x19]);
+        assert_eq!(&repaired[0x1D..], &archive[0x1D..]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn repair_layout_test() -> Result<(), Box<dyn Error>> {
+        let files: [(&[u8], u8); 2] = [(b"a.txt", 1), (b"b.txt", 0)];
+        let first_file_header = 0x10 + 2 * 4 + 4 + bfs2004a::HASH_SIZE as usize * 4;
+        let second_file_header = first_file_header + 0x16 + 5 + 4;
+        let header_end = second_file_header + 0x16 + 5;
+
+        // Made up Bfs2004a archive with two files sharing the same data, where the copy of the
+        // first file is not counted and the header end is off by four bytes
+        let mut archive = Vec::new();
+        archive.extend_from_slice(b"bfs1");
+        archive.extend_from_slice(&0x20040505u32.to_le_bytes());
+        archive.extend_from_slice(&(header_end as u32 + 4).to_le_bytes());
+        archive.extend_from_slice(&(files.len() as u32).to_le_bytes());
+        archive.extend_from_slice(&(first_file_header as u32).to_le_bytes());
+        archive.extend_from_slice(&(second_file_header as u32).to_le_bytes());
+        archive.extend_from_slice(&bfs2004a::HASH_SIZE.to_le_bytes());
+        archive.resize(first_file_header, 0);
+        for (file_name, copies) in files {
+            archive.push(0x00);
+            archive.push(0x00);
+            archive.extend_from_slice(&[0x00, 0x00]);
+            archive.extend_from_slice(&(header_end as u32).to_le_bytes());
+            archive.extend_from_slice(&4u32.to_le_bytes());
+            archive.extend_from_slice(&4u32.to_le_bytes());
+            archive.extend_from_slice(&0u32.to_le_bytes());
+            archive.extend_from_slice(&(file_name.len() as u16).to_le_bytes());
+            archive.extend_from_slice(file_name);
+            for _ in 0..copies {
+                archive.extend_from_slice(&(header_end as u32).to_le_bytes());
+            }
+        }
+        archive.extend_from_slice(b"data");
+        let temp_dir = TestDir::new("repair_layout_test")?;
+        let archive_path = temp_dir.join("archive.bfs");
+        fs::write(&archive_path, &archive)?;
+
+        let arguments = Arguments {
+            archive: archive_path.clone(),
+            force: false,
+            format: Some(Format::Bfs2004a),
+            dry_run: false,
+            backup: false,
+        };
+        run(arguments, io::sink())?;
+
+        let repaired = fs::read(&archive_path)?;
+
+        assert_eq!(&repaired[0x8..0xC], &(header_end as u32).to_le_bytes());
+        assert_eq!(repaired[first_file_header + 1], 1);
+        assert_eq!(repaired[second_file_header + 1], 0);
+        assert_eq!(
+            &repaired[0xC..first_file_header + 1],
+            &archive[0xC..first_file_header + 1]
+        );
+        assert_eq!(
+            &repaired[first_file_header + 2..],
+            &archive[first_file_header + 2..]
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,185 @@
+use std::error::Error;
+use std::io::{BufReader, Write};
+use std::path::PathBuf;
+
+use clap::{Parser, ValueEnum};
+use tabled::settings::object::{Columns, Segment};
+use tabled::settings::{Alignment, Modify, Style};
+use tabled::{Table, Tabled};
+
+use bfstool::archive_reader::open_archive_file;
+use bfstool::formats::partial::{probe_partial, read_partial};
+
+use crate::display::{display_offset, display_size};
+
+#[derive(Parser)]
+pub struct Arguments {
+    /// Archive file name
+    archive: PathBuf,
+    /// Archive format, detected from the magic and version if not provided
+    #[clap(short, long)]
+    format: Option<PartialFormat>,
+}
+
+/// Formats which can only be read on a best-effort basis
+#[derive(ValueEnum, Clone, Copy, Eq, PartialEq)]
+pub enum PartialFormat {
+    Bfs2011,
+    Bfs2013,
+}
+
+impl From<PartialFormat> for bfstool::Format {
+    fn from(value: PartialFormat) -> Self {
+        match value {
+            PartialFormat::Bfs2011 => bfstool::Format::Bfs2011,
+            PartialFormat::Bfs2013 => bfstool::Format::Bfs2013,
+        }
+    }
+}
+
+#[derive(Tabled)]
+struct Field {
+    #[tabled(rename = "Offset", display_with = "display_offset")]
+    offset: u64,
+
+    #[tabled(rename = "Value")]
+    value: String,
+
+    #[tabled(rename = "Status")]
+    status: &'static str,
+
+    #[tabled(rename = "Field")]
+    name: &'static str,
+}
+
+#[derive(Tabled)]
+struct File {
+    #[tabled(rename = "Header Offset", display_with = "display_offset")]
+    header_offset: u64,
+
+    #[tabled(rename = "Data Offset", display_with = "display_offset")]
+    data_offset: u64,
+
+    #[tabled(rename = "Size", display_with = "display_size")]
+    unpacked_size: u64,
+
+    #[tabled(rename = "Compressed", display_with = "display_size")]
+    packed_size: u64,
+}
+
+#[derive(Tabled)]
+struct UnknownRegion {
+    #[tabled(rename = "Start", display_with = "display_offset")]
+    start: u64,
+
+    #[tabled(rename = "End", display_with = "display_offset")]
+    end: u64,
+
+    #[tabled(rename = "Size", display_with = "display_size")]
+    size: u64,
+}
+
+pub fn run(arguments: Arguments, mut writer: impl Write) -> Result<(), Box<dyn Error>> {
+    let mut reader = BufReader::new(open_archive_file(&arguments.archive)?);
+
+    let format = match arguments.format {
+        Some(format) => format.into(),
+        None => probe_partial(&mut reader).ok_or("Unable to detect the archive format")?,
+    };
+    let archive = read_partial(&mut reader, format)?;
+
+    writeln!(
+        writer,
+        "Researching archive: {}",
+        arguments.archive.to_string_lossy()
+    )?;
+    writeln!(writer, "Format: {}", format)?;
+    writeln!(
+        writer,
+        "{}",
+        Table::new(archive.fields.iter().map(|field| Field {
+            offset: field.offset,
+            value: format!("{:08X}", field.value),
+            status: if field.confirmed {
+                "confirmed"
+            } else {
+                "assumed"
+            },
+            name: field.name,
+        }))
+        .with(Style::markdown())
+        .with(Modify::new(Segment::all()).with(Alignment::right()))
+        .with(Modify::new(Columns::last()).with(Alignment::left()))
+    )?;
+    writeln!(writer, "Files: {}", archive.files.len())?;
+    if !archive.files.is_empty() {
+        writeln!(
+            writer,
+            "{}",
+            Table::new(archive.files.iter().map(|file| File {
+                header_offset: file.header_offset,
+                data_offset: file.data_offset,
+                unpacked_size: file.unpacked_size,
+                packed_size: file.packed_size,
+            }))
+            .with(Style::markdown())
+            .with(Modify::new(Segment::all()).with(Alignment::right()))
+        )?;
+    }
+    writeln!(writer, "Unknown regions: {}", archive.unknown.len())?;
+    if !archive.unknown.is_empty() {
+        writeln!(
+            writer,
+            "{}",
+            Table::new(archive.unknown.iter().map(|region| UnknownRegion {
+                start: region.start,
+                end: region.end,
+                size: region.end - region.start,
+            }))
+            .with(Style::markdown())
+            .with(Modify::new(Segment::all()).with(Alignment::right()))
+        )?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use bfstool::formats::partial::BFS2013_VERSION;
+
+    use crate::test_archive::TestDir;
+
+    use super::*;
+
+    #[test]
+    fn research_test() -> Result<(), Box<dyn Error>> {
+        // Made up Bfs2013 archive with a 0x20 byte header
+        let mut archive = Vec::new();
+        archive.extend_from_slice(b"bbfs");
+        archive.extend_from_slice(&BFS2013_VERSION.to_le_bytes());
+        archive.extend_from_slice(&0x20u32.to_le_bytes());
+        archive.extend_from_slice(&1u32.to_le_bytes());
+        archive.resize(0x30, 0);
+
+        let temp_dir = TestDir::new("research_test")?;
+        let archive_path = temp_dir.join("archive.bin");
+        fs::write(&archive_path, archive)?;
+
+        let mut result = Vec::new();
+        let arguments = Arguments {
+            archive: archive_path,
+            format: None,
+        };
+        run(arguments, &mut result)?;
+
+        let result = String::from_utf8_lossy(&result);
+        assert!(result.contains("Format: bfs2013"));
+        assert!(result.contains("| 00000008 | 00000020 |   assumed | Header end |"));
+        assert!(result.contains("| 00000010 | 00000020 | 16 B |"));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,35 @@
+use std::error::Error;
+use std::io::Write;
+use std::path::PathBuf;
+
+use clap::Parser;
+
+use crate::exit_code::NotFound;
+use crate::messages::message;
+use crate::safe_write::restore_file;
+
+#[derive(Parser)]
+pub struct Arguments {
+    /// Archive file name
+    archive: PathBuf,
+}
+
+pub fn run(arguments: Arguments, mut writer: impl Write) -> Result<(), Box<dyn Error>> {
+    let Some(backup_path) = restore_file(&arguments.archive)? else {
+        return Err(NotFound(format!("Backup of {}", arguments.archive.to_string_lossy())).into());
+    };
+
+    writeln!(
+        writer,
+        "{}",
+        message(
+            "restore.restored",
+            &[
+                &arguments.archive.to_string_lossy(),
+                &backup_path.to_string_lossy()
+            ]
+        )
+    )?;
+
+    Ok(())
+}
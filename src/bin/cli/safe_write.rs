@@ -0,0 +1,456 @@
+use std::fs;
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::OnceLock;
+
+use crate::display::display_size;
+
+static TEMP_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+static NEXT_TEMP_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// Sets the directory temporary copies of modified files are stored in, instead of next to the
+/// modified files
+///
+/// Only the first call has any effect
+pub fn set_temp_dir(temp_dir: PathBuf) {
+    let _ = TEMP_DIR.set(temp_dir);
+}
+
+/// Modifies a file by applying `modify` to a copy of it, replacing the original file only once
+/// all modifications succeeded
+///
+/// A crash or error while modifying never leaves a partially modified file behind. While the file
+/// is being modified a `<file>.lock` file exists, which prevents other bfstool instances from
+/// modifying it at the same time. Read-only files are not modified.
+///
+/// The copy is stored next to the file as `<file>.tmp`, or in the directory set with
+/// [set_temp_dir] under a name unique to this process. Before copying, the free space is checked so large archives do not fail
+/// halfway through, and the copy is removed again if modifying fails.
+///
+/// If `backup` is true, the original file is kept as a backup, see [next_backup_path]
+pub fn modify_file(
+    path: &Path,
+    backup: bool,
+    modify: impl FnOnce(&mut File) -> io::Result<()>,
+) -> io::Result<()> {
+    modify_file_in(path, TEMP_DIR.get().map(PathBuf::as_path), backup, modify)
+}
+
+/// Modifies a file like [modify_file], storing the copy in the given directory if any
+fn modify_file_in(
+    path: &Path,
+    temp_dir: Option<&Path>,
+    backup: bool,
+    modify: impl FnOnce(&mut File) -> io::Result<()>,
+) -> io::Result<()> {
+    if fs::metadata(path)?.permissions().readonly() {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!("{} is read-only", path.to_string_lossy()),
+        ));
+    }
+
+    let _lock = LockFile::acquire(path)?;
+
+    let size = fs::metadata(path)?.len();
+    let directory = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let temp_file = match temp_dir {
+        Some(temp_dir) => {
+            fs::create_dir_all(temp_dir)?;
+            check_free_space(temp_dir, size)?;
+            if backup {
+                check_free_space(directory, size)?;
+            }
+            TempFile(unique_temp_path(path, temp_dir))
+        }
+        None => {
+            check_free_space(directory, if backup { size * 2 } else { size })?;
+            TempFile(sibling_path(path, "tmp"))
+        }
+    };
+
+    fs::copy(path, &temp_file.0)?;
+    OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(&temp_file.0)
+        .and_then(|mut file| {
+            modify(&mut file)?;
+            file.sync_all()
+        })?;
+
+    if backup {
+        fs::copy(path, next_backup_path(path))?;
+    }
+
+    temp_file.persist(path)
+}
+
+/// Checks whether the directory has at least `needed` bytes of free space
+fn check_free_space(directory: &Path, needed: u64) -> io::Result<()> {
+    let available = fs4::available_space(directory)?;
+    if available < needed {
+        return Err(io::Error::new(
+            io::ErrorKind::StorageFull,
+            format!(
+                "Not enough free space in {} - needed: {}, available: {}",
+                directory.to_string_lossy(),
+                display_size(&needed),
+                display_size(&available)
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Returns a path in `temp_dir` for a temporary copy of the file, which differs between processes
+/// and calls so copies of files with the same name never collide
+fn unique_temp_path(path: &Path, temp_dir: &Path) -> PathBuf {
+    temp_dir.join(sibling_path(
+        Path::new(path.file_name().unwrap_or_default()),
+        &format!(
+            "{}.{}.tmp",
+            process::id(),
+            NEXT_TEMP_ID.fetch_add(1, Ordering::Relaxed)
+        ),
+    ))
+}
+
+/// Temporary copy of a modified file, removed when dropped unless persisted
+struct TempFile(PathBuf);
+
+impl TempFile {
+    /// Replaces the file at `path` with the temporary copy
+    ///
+    /// If the copy is on a different file system than `path`, it is copied next to `path` as
+    /// `<file>.tmp` first, so the file is still replaced by a rename and never left partially
+    /// written. If that fails as well, the copy is kept and its path is part of the error.
+    fn persist(self, path: &Path) -> io::Result<()> {
+        if fs::rename(&self.0, path).is_ok() {
+            return Ok(());
+        }
+
+        let sibling = TempFile(sibling_path(path, "tmp"));
+        let result = fs::copy(&self.0, &sibling.0)
+            .and_then(|_| File::open(&sibling.0)?.sync_all())
+            .and_then(|_| fs::rename(&sibling.0, path));
+        result.map_err(|error| {
+            io::Error::new(
+                error.kind(),
+                format!(
+                    "Replacing {} failed, the modified copy is kept at {}: {}",
+                    path.to_string_lossy(),
+                    self.keep().to_string_lossy(),
+                    error
+                ),
+            )
+        })
+    }
+
+    /// Keeps the temporary copy instead of removing it, returning its path
+    fn keep(mut self) -> PathBuf {
+        std::mem::take(&mut self.0)
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        if !self.0.as_os_str().is_empty() {
+            let _ = fs::remove_file(&self.0);
+        }
+    }
+}
+
+/// Replaces a file with a copy of `source`, keeping the original file as a backup
+///
+/// Only the first replacement backs the file up, as `<file>.install.bak`, so the backup always is
+/// the original file and replacing the file again only replaces the previous copy. If no file
+/// existed before, a `<file>.added` marker is created instead. Both are used by [uninstall_file]
+/// and are separate from the backups of [modify_file].
+///
+/// The copy is made next to the file and passed to `verify` before it replaces the file, so a
+/// failed or corrupted copy never replaces the original file. Like [modify_file], a
+/// `<file>.lock` file exists while the file is being replaced.
+///
+/// Returns the path of the backup of the original file, or None if no file existed before
+pub fn replace_file(
+    path: &Path,
+    source: &Path,
+    verify: impl FnOnce(&Path) -> io::Result<()>,
+) -> io::Result<Option<PathBuf>> {
+    let _lock = LockFile::acquire(path)?;
+
+    let directory = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    check_free_space(directory, fs::metadata(source)?.len())?;
+
+    let temp_file = TempFile(sibling_path(path, "tmp"));
+    fs::copy(source, &temp_file.0)?;
+    File::open(&temp_file.0)?.sync_all()?;
+    verify(&temp_file.0)?;
+
+    let original_path = sibling_path(path, "install.bak");
+    let added_path = sibling_path(path, "added");
+    let replaced_before = original_path.exists() || added_path.exists();
+    if !replaced_before && path.exists() {
+        fs::rename(path, &original_path)?;
+        if let Err(error) = fs::rename(&temp_file.0, path) {
+            let _ = fs::rename(&original_path, path);
+            return Err(error);
+        }
+    } else {
+        if !replaced_before {
+            File::create(&added_path)?;
+        }
+        if let Err(error) = fs::rename(&temp_file.0, path) {
+            if !replaced_before {
+                let _ = fs::remove_file(&added_path);
+            }
+            return Err(error);
+        }
+    }
+
+    Ok(original_path.exists().then_some(original_path))
+}
+
+/// What [uninstall_file] did to undo [replace_file]
+#[derive(Debug, Eq, PartialEq)]
+pub enum Uninstalled {
+    /// The original file was restored from the backup at the path
+    Restored(PathBuf),
+    /// The file was removed, no file existed before it was replaced
+    Removed,
+}
+
+/// Undoes [replace_file], restoring the original file from `<file>.install.bak`, or removing the
+/// file if no file existed before
+///
+/// Returns None if the file was never replaced
+pub fn uninstall_file(path: &Path) -> io::Result<Option<Uninstalled>> {
+    let _lock = LockFile::acquire(path)?;
+
+    let original_path = sibling_path(path, "install.bak");
+    let added_path = sibling_path(path, "added");
+    if original_path.exists() {
+        fs::rename(&original_path, path)?;
+        Ok(Some(Uninstalled::Restored(original_path)))
+    } else if added_path.exists() {
+        match fs::remove_file(path) {
+            Err(error) if error.kind() != io::ErrorKind::NotFound => return Err(error),
+            _ => {}
+        }
+        fs::remove_file(&added_path)?;
+        Ok(Some(Uninstalled::Removed))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Restores a file from its latest backup, removing the backup
+///
+/// Returns the path of the restored backup, or None if there is no backup
+pub fn restore_file(path: &Path) -> io::Result<Option<PathBuf>> {
+    let _lock = LockFile::acquire(path)?;
+
+    let Some(backup_path) = latest_backup_path(path) else {
+        return Ok(None);
+    };
+    fs::rename(&backup_path, path)?;
+
+    Ok(Some(backup_path))
+}
+
+/// Returns the path of the `version`-th backup of a file
+///
+/// The first backup is stored as `<file>.bak`, later ones as `<file>.bak.<version>`
+fn backup_path(path: &Path, version: u32) -> PathBuf {
+    if version == 1 {
+        sibling_path(path, "bak")
+    } else {
+        sibling_path(path, &format!("bak.{}", version))
+    }
+}
+
+/// Returns the path of the newest existing backup of a file
+fn latest_backup_path(path: &Path) -> Option<PathBuf> {
+    (1..)
+        .map(|version| backup_path(path, version))
+        .take_while(|backup_path| backup_path.exists())
+        .last()
+}
+
+/// Returns the path a new backup of a file should be stored at
+pub fn next_backup_path(path: &Path) -> PathBuf {
+    (1..)
+        .map(|version| backup_path(path, version))
+        .find(|backup_path| !backup_path.exists())
+        .unwrap_or_else(|| sibling_path(path, "bak"))
+}
+
+/// Advisory lock held for as long as the value is alive
+struct LockFile(PathBuf);
+
+impl LockFile {
+    fn acquire(path: &Path) -> io::Result<Self> {
+        let lock_path = sibling_path(path, "lock");
+        match OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&lock_path)
+        {
+            Ok(_) => Ok(Self(lock_path)),
+            Err(error) if error.kind() == io::ErrorKind::AlreadyExists => Err(io::Error::new(
+                io::ErrorKind::WouldBlock,
+                format!(
+                    "{} is being modified by another process, remove {} if that is not the case",
+                    path.to_string_lossy(),
+                    lock_path.to_string_lossy()
+                ),
+            )),
+            Err(error) => Err(error),
+        }
+    }
+}
+
+impl Drop for LockFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+/// Returns the path with `.<extension>` appended
+fn sibling_path(path: &Path, extension: &str) -> PathBuf {
+    let mut file_name = path.as_os_str().to_owned();
+    file_name.push(".");
+    file_name.push(extension);
+    PathBuf::from(file_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use pretty_assertions::assert_eq;
+
+    use crate::test_archive::TestDir;
+
+    use super::*;
+
+    #[test]
+    fn modify_file_test() -> io::Result<()> {
+        let temp_dir = TestDir::new("safe_write_test")?;
+        let path = temp_dir.join("archive.bin");
+        fs::write(&path, b"bfs1")?;
+
+        let result = modify_file(&path, false, |file| {
+            file.write_all(b"bzf2")?;
+            Err(io::ErrorKind::Other.into())
+        });
+        assert!(result.is_err());
+        assert_eq!(fs::read(&path)?, b"bfs1");
+
+        modify_file(&path, false, |file| file.write_all(b"bzf2"))?;
+        assert_eq!(fs::read(&path)?, b"bzf2");
+        assert!(!sibling_path(&path, "lock").exists());
+        assert!(!sibling_path(&path, "tmp").exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn temp_dir_test() -> io::Result<()> {
+        let test_dir = TestDir::new("temp_dir_test")?;
+        let path = test_dir.join("archive.bin");
+        let temp_dir = test_dir.join("temp");
+        fs::write(&path, b"bfs1")?;
+
+        let result = modify_file_in(&path, Some(&temp_dir), false, |file| {
+            file.write_all(b"bzf2")?;
+            Err(io::ErrorKind::Other.into())
+        });
+        assert!(result.is_err());
+        assert_eq!(fs::read(&path)?, b"bfs1");
+        assert_eq!(fs::read_dir(&temp_dir)?.count(), 0);
+
+        modify_file_in(&path, Some(&temp_dir), false, |file| {
+            file.write_all(b"bzf2")
+        })?;
+        assert_eq!(fs::read(&path)?, b"bzf2");
+        assert_eq!(fs::read_dir(&temp_dir)?.count(), 0);
+        assert!(!sibling_path(&path, "tmp").exists());
+
+        assert_eq!(
+            check_free_space(&std::env::temp_dir(), u64::MAX).map_err(|error| error.kind()),
+            Err(io::ErrorKind::StorageFull)
+        );
+        assert_ne!(
+            unique_temp_path(&path, &temp_dir),
+            unique_temp_path(&path, &temp_dir)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn backup_test() -> io::Result<()> {
+        let temp_dir = TestDir::new("backup_test")?;
+        let path = temp_dir.join("archive.bin");
+        fs::write(&path, b"bfs1")?;
+
+        modify_file(&path, true, |file| file.write_all(b"bzf2"))?;
+        modify_file(&path, true, |file| file.write_all(b"bbzf"))?;
+        assert_eq!(fs::read(&path)?, b"bbzf");
+        assert_eq!(fs::read(backup_path(&path, 1))?, b"bfs1");
+        assert_eq!(fs::read(backup_path(&path, 2))?, b"bzf2");
+
+        assert_eq!(restore_file(&path)?, Some(backup_path(&path, 2)));
+        assert_eq!(fs::read(&path)?, b"bzf2");
+        assert_eq!(restore_file(&path)?, Some(backup_path(&path, 1)));
+        assert_eq!(fs::read(&path)?, b"bfs1");
+        assert_eq!(restore_file(&path)?, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn replace_file_test() -> io::Result<()> {
+        let temp_dir = TestDir::new("replace_file_test")?;
+        let path = temp_dir.join("archive.bin");
+        let source = temp_dir.join("source.bin");
+        fs::write(&path, b"bfs1")?;
+        fs::write(&source, b"bbzf")?;
+
+        modify_file(&path, true, |file| file.write_all(b"bzf2"))?;
+        let original_path = sibling_path(&path, "install.bak");
+        assert_eq!(
+            replace_file(&path, &source, |_| Ok(()))?,
+            Some(original_path.clone())
+        );
+        assert_eq!(
+            replace_file(&path, &source, |_| Ok(()))?,
+            Some(original_path.clone())
+        );
+        assert_eq!(fs::read(&path)?, b"bbzf");
+        assert_eq!(fs::read(backup_path(&path, 1))?, b"bfs1");
+
+        assert_eq!(
+            uninstall_file(&path)?,
+            Some(Uninstalled::Restored(original_path))
+        );
+        assert_eq!(fs::read(&path)?, b"bzf2");
+        assert_eq!(uninstall_file(&path)?, None);
+        assert_eq!(restore_file(&path)?, Some(backup_path(&path, 1)));
+        assert_eq!(fs::read(&path)?, b"bfs1");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,195 @@
+use std::collections::HashSet;
+use std::error::Error;
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::{BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use clap::Parser;
+use tabled::settings::object::{Columns, Segment};
+use tabled::settings::{Alignment, Modify, Style};
+use tabled::{Table, Tabled};
+
+use bfstool::{read_archive_file_with_options, Format};
+
+use crate::config;
+use crate::display::display_size;
+use crate::progress::file_bar;
+use crate::warning::{warn, warn_archive};
+
+#[derive(Parser)]
+pub struct Arguments {
+    /// Game installation directory
+    game_dir: PathBuf,
+    /// Extract all found archives into the given directory, each into a folder named after its
+    /// path inside the game directory
+    #[clap(long)]
+    extract: Option<PathBuf>,
+}
+
+#[derive(Tabled)]
+struct FoundArchive {
+    #[tabled(rename = "Format")]
+    format: Format,
+
+    #[tabled(rename = "Files")]
+    files: String,
+
+    #[tabled(rename = "Size", display_with = "display_size")]
+    size: u64,
+
+    #[tabled(rename = "Archive")]
+    path: String,
+}
+
+pub fn run(arguments: Arguments, mut writer: impl Write) -> Result<(), Box<dyn Error>> {
+    let archives = find_archives(&arguments.game_dir)?;
+
+    let mut found_archives = Vec::new();
+    let mut extracted_files = 0;
+    for (path, format) in archives {
+        let relative_path = path.strip_prefix(&arguments.game_dir).unwrap_or(&path);
+        let mut found_archive = FoundArchive {
+            format,
+            files: "-".to_string(),
+            size: fs::metadata(&path)?.len(),
+            path: relative_path.to_string_lossy().to_string(),
+        };
+
+        match read_archive_file_with_options(&path, format, config::read_options(false)) {
+            Ok(mut archive) => {
+                warn_archive(&path, archive.as_ref());
+                found_archive.files = archive.file_count().to_string();
+                if let Some(output) = &arguments.extract {
+                    let file_names = archive.file_names();
+                    let bar = file_bar(file_names.len() as u64);
+                    archive.extract_files(
+                        file_names,
+                        &output.join(relative_path),
+                        Box::new(|_, _| bar.inc(1)),
+                    )?;
+                    bar.finish_and_clear();
+                    extracted_files += archive.file_count();
+                }
+            }
+            Err(error) => warn(format!("{}: {}", path.to_string_lossy(), error)),
+        }
+
+        found_archives.push(found_archive);
+    }
+
+    writeln!(
+        writer,
+        "Scanned directory: {}",
+        arguments.game_dir.to_string_lossy()
+    )?;
+    writeln!(writer, "Archives found: {}", found_archives.len())?;
+    if arguments.extract.is_some() {
+        writeln!(writer, "Extracted files: {}", extracted_files)?;
+    }
+    if !found_archives.is_empty() {
+        writeln!(
+            writer,
+            "{}",
+            Table::new(found_archives)
+                .with(Style::markdown())
+                .with(Modify::new(Segment::all()).with(Alignment::right()))
+                .with(Modify::new(Columns::last()).with(Alignment::left()))
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Finds all archives in the directory and its subdirectories by their headers, regardless of
+/// their extensions
+///
+/// Returns the archive paths and formats, sorted by path
+fn find_archives(directory: &Path) -> io::Result<Vec<(PathBuf, Format)>> {
+    let mut archives = Vec::new();
+    find_archives_in(directory, &mut HashSet::new(), &mut archives)?;
+    archives.sort_by(|a, b| a.0.cmp(&b.0));
+
+    Ok(archives)
+}
+
+/// Adds the archives in the directory and its subdirectories to `archives`, skipping directories
+/// already visited through a symbolic link and warning about files which can not be opened
+fn find_archives_in(
+    directory: &Path,
+    visited: &mut HashSet<PathBuf>,
+    archives: &mut Vec<(PathBuf, Format)>,
+) -> io::Result<()> {
+    if !visited.insert(fs::canonicalize(directory)?) {
+        return Ok(());
+    }
+    for entry in fs::read_dir(directory)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            find_archives_in(&path, visited, archives)?;
+            continue;
+        }
+        match File::open(&path) {
+            Ok(file) => {
+                if let Some(format) = bfstool::probe(&mut BufReader::new(file)) {
+                    archives.push((path, format));
+                }
+            }
+            Err(error) => warn(format!("{}: {}", path.to_string_lossy(), error)),
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::test_archive::TestDir;
+
+    use super::*;
+
+    #[test]
+    fn find_archives_test() -> io::Result<()> {
+        let archives = find_archives(Path::new("test_data/bzf2002"))?;
+
+        assert_eq!(
+            archives,
+            vec![
+                (
+                    PathBuf::from("test_data/bzf2002/demo_Shader.bin"),
+                    Format::Bzf2002
+                ),
+                (
+                    PathBuf::from("test_data/bzf2002/tt_Language.bin"),
+                    Format::Bzf2002
+                ),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn find_archives_symlink_loop_test() -> io::Result<()> {
+        let temp_dir = TestDir::new("find_archives_symlink_loop_test")?;
+        let data_dir = temp_dir.join("data");
+        fs::create_dir_all(&data_dir)?;
+        fs::copy(
+            "test_data/bzf2002/tt_Language.bin",
+            data_dir.join("language.bzf"),
+        )?;
+        std::os::unix::fs::symlink(&temp_dir.path, data_dir.join("loop"))?;
+        std::os::unix::fs::symlink(data_dir.join("missing.bzf"), data_dir.join("broken.bzf"))?;
+
+        assert_eq!(
+            find_archives(&temp_dir.path)?,
+            vec![(data_dir.join("language.bzf"), Format::Bzf2002)]
+        );
+
+        Ok(())
+    }
+}
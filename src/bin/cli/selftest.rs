@@ -0,0 +1,288 @@
+use std::error::Error;
+use std::io::{BufWriter, Cursor, Write};
+
+use clap::Parser;
+use tabled::settings::object::Segment;
+use tabled::settings::{Alignment, Modify, Style};
+use tabled::{Table, Tabled};
+
+use bfstool::archive_reader::ReadError;
+use bfstool::compression::{compress, decompress, jamcrc};
+use bfstool::{read_archive, ArchivedFileInfo, CompressionMethod, Format};
+
+#[derive(Parser)]
+pub struct Arguments {}
+
+/// Archive headers from test_data embedded into the binary, with known contents
+///
+/// The Bfs2004b and Bfs2007 samples are a few hundred KB each, they are only embedded with the
+/// `selftest_vectors` feature
+struct TestVector {
+    name: &'static str,
+    data: &'static [u8],
+    format: Format,
+    file_count: u64,
+    first_file_name: &'static str,
+    first_file_info: ArchivedFileInfo,
+}
+
+#[derive(Tabled)]
+struct SelfTestResult {
+    #[tabled(rename = "Test")]
+    test: String,
+
+    #[tabled(rename = "Result")]
+    result: String,
+}
+
+fn test_vectors() -> Vec<TestVector> {
+    let mut test_vectors = vec![TestVector {
+        name: "bfs2004a/europe.bin",
+        data: include_bytes!("../../../test_data/bfs2004a/europe.bin"),
+        format: Format::Bfs2004a,
+        file_count: 1,
+        first_file_name: "data/language/version.ini",
+        first_file_info: ArchivedFileInfo {
+            offset: 0xFDC,
+            compression_method: CompressionMethod::Zlib,
+            size: 0x44F,
+            compressed_size: 0x1D7,
+            copies: 0,
+            copies_offsets: vec![],
+            hash: Some(0xF6260C6E),
+        },
+    }];
+    #[cfg(feature = "selftest_vectors")]
+    test_vectors.extend([
+        TestVector {
+            name: "bfs2004b/fo2a.bin",
+            data: include_bytes!("../../../test_data/bfs2004b/fo2a.bin"),
+            format: Format::Bfs2004b,
+            file_count: 6349,
+            first_file_name: "data/tracks/fields/fields1/c/lighting/shadowmap_w2.dat",
+            first_file_info: ArchivedFileInfo {
+                offset: 0x623AD335,
+                compression_method: CompressionMethod::Zlib,
+                size: 0x40000,
+                compressed_size: 0x12664,
+                copies: 0,
+                copies_offsets: vec![],
+                hash: Some(0x487CE316),
+            },
+        },
+        TestVector {
+            name: "bfs2007/fouc_data.bin",
+            data: include_bytes!("../../../test_data/bfs2007/fouc_data.bin"),
+            format: Format::Bfs2007,
+            file_count: 9567,
+            first_file_name: "data/tracks/racing/textures/rac_lamppost4.dds",
+            first_file_info: ArchivedFileInfo {
+                offset: 0x86B1065A,
+                compression_method: CompressionMethod::Zlib,
+                size: 0xAB38,
+                compressed_size: 0x8749,
+                copies: 0,
+                copies_offsets: vec![],
+                hash: Some(0x22434A64),
+            },
+        },
+    ]);
+    test_vectors.extend([
+        TestVector {
+            name: "bzf2001/language.bin",
+            data: include_bytes!("../../../test_data/bzf2001/language.bin"),
+            format: Format::Bzf2001,
+            file_count: 4,
+            first_file_name: "credits.txt",
+            first_file_info: ArchivedFileInfo {
+                offset: 0xE0,
+                compression_method: CompressionMethod::Zlib,
+                size: 0xF5F,
+                compressed_size: 0x78D,
+                copies: 0,
+                copies_offsets: vec![],
+                hash: None,
+            },
+        },
+        TestVector {
+            name: "bzf2002/demo_Shader.bin",
+            data: include_bytes!("../../../test_data/bzf2002/demo_Shader.bin"),
+            format: Format::Bzf2002,
+            file_count: 26,
+            first_file_name: "fix_car_body.sha",
+            first_file_info: ArchivedFileInfo {
+                offset: 0x420,
+                compression_method: CompressionMethod::Zlib,
+                size: 0x123C,
+                compressed_size: 0x3B8,
+                copies: 0,
+                copies_offsets: vec![],
+                hash: None,
+            },
+        },
+    ]);
+    test_vectors
+}
+
+pub fn run(_arguments: Arguments, mut writer: impl Write) -> Result<(), Box<dyn Error>> {
+    let mut results = Vec::new();
+    let mut record = |test: String, result: Result<(), String>| {
+        results.push(SelfTestResult {
+            test,
+            result: match result {
+                Ok(()) => "ok".to_string(),
+                Err(error) => format!("FAILED: {}", error),
+            },
+        })
+    };
+
+    for test_vector in test_vectors() {
+        record(
+            format!("read {}", test_vector.name),
+            check_test_vector(&test_vector),
+        );
+    }
+    record(
+        "bzf2001 encryption round trip".to_string(),
+        check_bzf2001_crypt(),
+    );
+    for method in [CompressionMethod::Zlib, CompressionMethod::Zstd] {
+        record(
+            format!("{} compression round trip", method),
+            check_compression(method),
+        );
+    }
+    record("JAMCRC check value".to_string(), check_jamcrc());
+
+    let failed = results
+        .iter()
+        .filter(|result| result.result != "ok")
+        .count();
+
+    writeln!(
+        writer,
+        "{}",
+        Table::new(results)
+            .with(Style::markdown())
+            .with(Modify::new(Segment::all()).with(Alignment::left()))
+    )?;
+
+    if failed > 0 {
+        return Err(format!("{} self-test(s) failed", failed).into());
+    }
+
+    Ok(())
+}
+
+fn check_test_vector(test_vector: &TestVector) -> Result<(), String> {
+    let mut reader = Cursor::new(test_vector.data);
+    let detected = bfstool::probe(&mut reader);
+    if detected != Some(test_vector.format) {
+        return Err(format!(
+            "detected {:?}, expected {}",
+            detected, test_vector.format
+        ));
+    }
+
+    let archive =
+        read_archive(reader, test_vector.format, false).map_err(|error| error.to_string())?;
+    if archive.file_count() != test_vector.file_count {
+        return Err(format!(
+            "{} files, expected {}",
+            archive.file_count(),
+            test_vector.file_count
+        ));
+    }
+    let file_names = archive.file_names();
+    if file_names.first().map(String::as_str) != Some(test_vector.first_file_name) {
+        return Err(format!(
+            "first file is {:?}, expected {}",
+            file_names.first(),
+            test_vector.first_file_name
+        ));
+    }
+    let file_info = archive.file_info(test_vector.first_file_name);
+    if file_info.first() != Some(&test_vector.first_file_info) {
+        return Err(format!("unexpected file info {:?}", file_info.first()));
+    }
+
+    Ok(())
+}
+
+fn check_bzf2001_crypt() -> Result<(), String> {
+    let original: &'static [u8] = include_bytes!("../../../test_data/bzf2001/language.bin");
+    // Made up key, the real one is not distributed with bfstool
+    let key = std::array::from_fn(|index| (index as u8).wrapping_mul(7).wrapping_add(3));
+
+    let mut encrypted = BufWriter::new(Cursor::new(Vec::new()));
+    bfstool::crypt::bzf2001::encrypt(Cursor::new(original), &mut encrypted, key)
+        .map_err(|error| error.to_string())?;
+    let encrypted = encrypted
+        .into_inner()
+        .map_err(|error| error.to_string())?
+        .into_inner();
+
+    if !matches!(
+        read_archive(Cursor::new(encrypted.clone()), Format::Bzf2001, false),
+        Err(ReadError::LikelyEncrypted { .. })
+    ) {
+        return Err("encrypted archive not detected as encrypted".to_string());
+    }
+
+    let mut decrypted = BufWriter::new(Cursor::new(Vec::new()));
+    bfstool::crypt::bzf2001::decrypt(Cursor::new(encrypted), &mut decrypted, key)
+        .map_err(|error| error.to_string())?;
+    let decrypted = decrypted
+        .into_inner()
+        .map_err(|error| error.to_string())?
+        .into_inner();
+
+    if decrypted != original {
+        return Err("decrypted archive differs from the original".to_string());
+    }
+
+    Ok(())
+}
+
+fn check_compression(method: CompressionMethod) -> Result<(), String> {
+    let original = include_bytes!("../../../test_data/bfs2004a/europe.bin");
+
+    let mut compressed = Vec::new();
+    let compressed_size = compress(&mut original.as_slice(), &mut compressed, method, None)
+        .map_err(|error| error.to_string())?;
+    let mut decompressed = Vec::new();
+    decompress(
+        &mut compressed.as_slice(),
+        &mut decompressed,
+        compressed_size,
+        method,
+    )
+    .map_err(|error| error.to_string())?;
+
+    if decompressed != original {
+        return Err("decompressed data differs from the original".to_string());
+    }
+
+    Ok(())
+}
+
+fn check_jamcrc() -> Result<(), String> {
+    let crc = jamcrc(b"123456789");
+    if crc != 0x340BC6D9 {
+        return Err(format!("got {:08X}, expected 340BC6D9", crc));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::*;
+
+    #[test]
+    fn selftest_test() -> Result<(), Box<dyn Error>> {
+        run(Arguments {}, io::sink())
+    }
+}
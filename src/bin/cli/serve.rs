@@ -0,0 +1,346 @@
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs::File;
+use std::io;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::path::PathBuf;
+use std::thread;
+
+use clap::{Parser, ValueEnum};
+use serde::{Deserialize, Deserializer};
+use serde_json::{json, Value};
+
+use bfstool::archive_reader::ArchiveReader;
+use bfstool::read_archive_file_with_options;
+
+use crate::config;
+use crate::list::{sort_files, JsonFileInfo, Order, TableFileInfo};
+use crate::warning::{warn, warn_archive};
+
+use super::Format;
+
+/// Invalid JSON was received
+const PARSE_ERROR: i64 = -32700;
+/// The requested method does not exist
+const METHOD_NOT_FOUND: i64 = -32601;
+/// Invalid method parameters
+const INVALID_PARAMS: i64 = -32602;
+/// The method failed
+const SERVER_ERROR: i64 = -32000;
+
+#[derive(Parser)]
+pub struct Arguments {
+    /// Address to listen on, requests are not authenticated so only loopback addresses are
+    /// allowed
+    #[clap(long, default_value = "127.0.0.1:7447")]
+    address: String,
+}
+
+/// A JSON-RPC 2.0 request, sent as a single line
+#[derive(Deserialize)]
+struct Request {
+    /// Id of the request, notifications have none and are not answered
+    #[serde(default, deserialize_with = "deserialize_id")]
+    id: Option<Value>,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+/// Deserializes a present id as Some, even if it is `null`, so only absent ids are None
+fn deserialize_id<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Value>, D::Error> {
+    Value::deserialize(deserializer).map(Some)
+}
+
+#[derive(Deserialize)]
+struct ArchiveParams {
+    archive: PathBuf,
+    format: String,
+    #[serde(default)]
+    force: bool,
+}
+
+#[derive(Deserialize)]
+struct ListParams {
+    #[serde(flatten)]
+    archive: ArchiveParams,
+    /// Column to sort the files by, header order if not present
+    order: Option<String>,
+    #[serde(default)]
+    descending: bool,
+}
+
+#[derive(Deserialize)]
+struct ExtractParams {
+    #[serde(flatten)]
+    archive: ArchiveParams,
+    output: PathBuf,
+    /// Files to extract, all files if not present
+    files: Option<Vec<String>>,
+}
+
+#[derive(Deserialize)]
+struct CloseParams {
+    archive: PathBuf,
+    /// Format the archive was opened as, the archive is closed for all formats if not present
+    format: Option<String>,
+}
+
+/// Archives kept open between requests of a connection, by path and format
+type OpenArchives = HashMap<(PathBuf, bfstool::Format), Box<dyn ArchiveReader<BufReader<File>>>>;
+
+/// Error code and message of a failed request
+type RequestError = (i64, String);
+
+pub fn run(arguments: Arguments, mut writer: impl Write) -> Result<(), Box<dyn Error>> {
+    // Anyone able to connect can read and write files as the user running the server
+    if arguments
+        .address
+        .to_socket_addrs()?
+        .any(|address| !address.ip().is_loopback())
+    {
+        return Err(format!(
+            "Refusing to listen on {}, only loopback addresses are allowed",
+            arguments.address
+        )
+        .into());
+    }
+
+    let listener = TcpListener::bind(&arguments.address)?;
+    writeln!(writer, "Listening on {}", listener.local_addr()?)?;
+
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                thread::spawn(move || {
+                    if let Err(error) = handle_connection(&stream) {
+                        warn(format!("Connection failed: {}", error));
+                    }
+                });
+            }
+            Err(error) => warn(format!("Connection failed: {}", error)),
+        }
+    }
+
+    Ok(())
+}
+
+/// Handles the requests of a single client, every client has its own open archives
+fn handle_connection(stream: &TcpStream) -> io::Result<()> {
+    let mut writer = stream;
+    let mut archives = OpenArchives::new();
+    for line in BufReader::new(stream).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(response) = handle_request(&line, &mut archives) {
+            serde_json::to_writer(&mut writer, &response)?;
+            writeln!(writer)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Handles a single JSON-RPC request, returning the response, or None if the request is a
+/// notification
+///
+/// Supported methods:
+/// - `list` - `{archive, format, force?, order?, descending?}` - lists all files in the archive,
+///   sorted like with `list --order`, files with equal values are kept in header order
+/// - `extract` - `{archive, format, force?, output, files?}` - extracts files from the archive
+/// - `close` - `{archive, format?}` - closes the archive, it is opened again by the next request
+///   using it
+fn handle_request(line: &str, archives: &mut OpenArchives) -> Option<Value> {
+    let request = match serde_json::from_str::<Request>(line) {
+        Ok(request) => request,
+        Err(error) => {
+            return Some(error_response(
+                Value::Null,
+                (PARSE_ERROR, error.to_string()),
+            ))
+        }
+    };
+
+    let result = match request.method.as_str() {
+        "list" => parse_params(request.params).and_then(|params| list(&params, archives)),
+        "extract" => parse_params(request.params).and_then(|params| extract(params, archives)),
+        "close" => parse_params(request.params).and_then(|params| close(params, archives)),
+        method => Err((METHOD_NOT_FOUND, format!("Unknown method: {}", method))),
+    };
+
+    let id = request.id?;
+    Some(match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(error) => error_response(id, error),
+    })
+}
+
+fn error_response(id: Value, (code, message): RequestError) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+fn parse_params<T: for<'de> Deserialize<'de>>(params: Value) -> Result<T, RequestError> {
+    serde_json::from_value(params).map_err(|error| (INVALID_PARAMS, error.to_string()))
+}
+
+fn open_archive<'a>(
+    params: &ArchiveParams,
+    archives: &'a mut OpenArchives,
+) -> Result<&'a mut Box<dyn ArchiveReader<BufReader<File>>>, RequestError> {
+    let format = parse_format(&params.format)?;
+    match archives.entry((params.archive.clone(), format)) {
+        Entry::Occupied(entry) => Ok(entry.into_mut()),
+        Entry::Vacant(entry) => {
+            let archive = read_archive_file_with_options(
+                &params.archive,
+                format,
+                config::read_options(params.force),
+            )
+            .map_err(|error| (SERVER_ERROR, error.to_string()))?;
+            warn_archive(&params.archive, archive.as_ref());
+            Ok(entry.insert(archive))
+        }
+    }
+}
+
+fn parse_format(format: &str) -> Result<bfstool::Format, RequestError> {
+    Format::from_str(format, true)
+        .map(bfstool::Format::from)
+        .map_err(|error| (INVALID_PARAMS, error))
+}
+
+fn list(params: &ListParams, archives: &mut OpenArchives) -> Result<Value, RequestError> {
+    let order = params
+        .order
+        .as_deref()
+        .map(|order| Order::from_str(order, true))
+        .transpose()
+        .map_err(|error| (INVALID_PARAMS, error))?;
+    let archive = open_archive(&params.archive, archives)?;
+    let mut files = archive
+        .multiple_file_info(archive.file_names())
+        .into_iter()
+        .enumerate()
+        .map(|(index, (file_name, file_info))| TableFileInfo {
+            index,
+            method: file_info.compression_method,
+            size: file_info.size,
+            compressed: file_info.compressed_size,
+            copies: file_info.copies,
+            offset: file_info.offset,
+            file_name,
+            hash: file_info.hash,
+        })
+        .collect::<Vec<_>>();
+    if let Some(order) = order {
+        sort_files(&mut files, order, params.descending);
+    }
+    serde_json::to_value(files.iter().map(JsonFileInfo::from).collect::<Vec<_>>())
+        .map_err(|error| (SERVER_ERROR, error.to_string()))
+}
+
+fn extract(params: ExtractParams, archives: &mut OpenArchives) -> Result<Value, RequestError> {
+    let archive = open_archive(&params.archive, archives)?;
+    let file_names = params.files.unwrap_or_else(|| archive.file_names());
+    let file_count = file_names.len();
+    archive
+        .extract_files(file_names, &params.output, Box::new(|_, _| {}))
+        .map_err(|error| (SERVER_ERROR, error.to_string()))?;
+    Ok(json!({ "extracted": file_count }))
+}
+
+fn close(params: CloseParams, archives: &mut OpenArchives) -> Result<Value, RequestError> {
+    let format = params.format.as_deref().map(parse_format).transpose()?;
+    let open_archives = archives.len();
+    archives.retain(|(archive, archive_format), _| {
+        *archive != params.archive || format.is_some_and(|format| format != *archive_format)
+    });
+    Ok(json!({ "closed": archives.len() < open_archives }))
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn list_request_test() {
+        let mut archives = OpenArchives::new();
+        let response = handle_request(
+            r#"{"jsonrpc":"2.0","id":1,"method":"list","params":{"archive":"test_data/bfs2004a/europe.bin","format":"bfs2004a"}}"#,
+            &mut archives,
+        )
+        .unwrap();
+
+        assert_eq!(response["id"], 1);
+        assert_eq!(response["result"][0]["offset"], 0xFDC);
+        assert_eq!(response["result"][0]["compressed_size"], 0x1D7);
+        assert_eq!(archives.len(), 1);
+
+        let response = handle_request(
+            r#"{"jsonrpc":"2.0","id":3,"method":"list","params":{"archive":"test_data/bfs2004a/europe.bin","format":"bfs2004a","order":"offset","descending":true}}"#,
+            &mut archives,
+        )
+        .unwrap();
+
+        let offsets = response["result"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|file| file["offset"].as_u64().unwrap())
+            .collect::<Vec<_>>();
+        assert!(offsets.windows(2).all(|pair| pair[0] >= pair[1]));
+
+        let response = handle_request(
+            r#"{"jsonrpc":"2.0","id":2,"method":"close","params":{"archive":"test_data/bfs2004a/europe.bin"}}"#,
+            &mut archives,
+        )
+        .unwrap();
+
+        assert_eq!(response["result"]["closed"], true);
+        assert!(archives.is_empty());
+    }
+
+    #[test]
+    fn non_loopback_address_test() {
+        let arguments = Arguments {
+            address: "0.0.0.0:0".to_string(),
+        };
+        assert!(run(arguments, io::sink()).is_err());
+    }
+
+    #[test]
+    fn unknown_method_test() {
+        let response = handle_request(
+            r#"{"jsonrpc":"2.0","id":1,"method":"patch"}"#,
+            &mut OpenArchives::new(),
+        )
+        .unwrap();
+
+        assert_eq!(response["error"]["code"], METHOD_NOT_FOUND);
+    }
+
+    #[test]
+    fn notification_test() {
+        let mut archives = OpenArchives::new();
+        let response = handle_request(
+            r#"{"jsonrpc":"2.0","method":"list","params":{"archive":"test_data/bfs2004a/europe.bin","format":"bfs2004a"}}"#,
+            &mut archives,
+        );
+
+        assert_eq!(response, None);
+        assert_eq!(archives.len(), 1);
+
+        let response = handle_request(
+            r#"{"jsonrpc":"2.0","id":null,"method":"patch"}"#,
+            &mut archives,
+        );
+
+        assert_eq!(response.unwrap()["id"], Value::Null);
+    }
+}
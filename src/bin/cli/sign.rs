@@ -0,0 +1,106 @@
+use std::error::Error;
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use clap::Parser;
+use ed25519_dalek::{Signer, SigningKey};
+use rand_core::OsRng;
+use serde::{Deserialize, Serialize};
+
+#[derive(Parser)]
+pub struct Arguments {
+    /// Archive file name
+    archive: PathBuf,
+    /// File containing the hex-encoded Ed25519 secret key
+    #[clap(long)]
+    key: PathBuf,
+    /// Generate a new secret key and store it in the key file instead of reading it
+    #[clap(long)]
+    new_key: bool,
+}
+
+/// Detached signature of an archive, stored next to it as `<archive>.sig`
+#[derive(Serialize, Deserialize)]
+pub struct ArchiveSignature {
+    /// Hex-encoded Ed25519 public key of the signer
+    pub public_key: String,
+    /// Hex-encoded BLAKE3 hash of the archive
+    pub blake3: String,
+    /// Hex-encoded Ed25519 signature of the BLAKE3 hash
+    pub signature: String,
+}
+
+pub fn run(arguments: Arguments, mut writer: impl Write) -> Result<(), Box<dyn Error>> {
+    let signing_key = if arguments.new_key {
+        let signing_key = SigningKey::generate(&mut OsRng);
+        create_key_file(&arguments.key)?
+            .write_all(hex::encode(signing_key.to_bytes()).as_bytes())?;
+        signing_key
+    } else {
+        let secret_key: [u8; 32] = hex::decode(fs::read_to_string(&arguments.key)?.trim())?
+            .try_into()
+            .map_err(|_| "The secret key has to be 32 bytes long")?;
+        SigningKey::from_bytes(&secret_key)
+    };
+
+    let hash = hash_file(&arguments.archive)?;
+    let signature = signing_key.sign(hash.as_bytes());
+    let archive_signature = ArchiveSignature {
+        public_key: hex::encode(signing_key.verifying_key().to_bytes()),
+        blake3: hash.to_hex().to_string(),
+        signature: hex::encode(signature.to_bytes()),
+    };
+
+    let signature_path = signature_path(&arguments.archive);
+    fs::write(&signature_path, toml::to_string(&archive_signature)?)?;
+
+    writeln!(
+        writer,
+        "Signed archive: {}",
+        arguments.archive.to_string_lossy()
+    )?;
+    writeln!(writer, "Public key: {}", archive_signature.public_key)?;
+    writeln!(writer, "Signature: {}", signature_path.to_string_lossy())?;
+
+    Ok(())
+}
+
+/// Creates a new key file only readable by the current user, failing if the file already exists
+fn create_key_file(path: &Path) -> Result<File, Box<dyn Error>> {
+    let mut options = File::options();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+
+        options.mode(0o600);
+    }
+    options.open(path).map_err(|error| {
+        if error.kind() == io::ErrorKind::AlreadyExists {
+            format!(
+                "{} already exists, refusing to overwrite it",
+                path.to_string_lossy()
+            )
+            .into()
+        } else {
+            error.into()
+        }
+    })
+}
+
+/// Returns the path of the detached signature of an archive
+pub fn signature_path(archive: &Path) -> PathBuf {
+    let mut signature_path = archive.as_os_str().to_owned();
+    signature_path.push(".sig");
+    PathBuf::from(signature_path)
+}
+
+/// Calculates the BLAKE3 hash of a file
+pub fn hash_file(path: &Path) -> io::Result<blake3::Hash> {
+    let mut hasher = blake3::Hasher::new();
+    io::copy(&mut File::open(path)?, &mut hasher)?;
+    Ok(hasher.finalize())
+}
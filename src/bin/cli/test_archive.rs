@@ -0,0 +1,77 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Offset of the data of the only file in europe.bin, data/language/version.ini
+const EUROPE_DATA_OFFSET: u64 = 0xFDC;
+
+/// Size of the data of the only file in europe.bin
+const EUROPE_DATA_SIZE: u64 = 0x1D7;
+
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// Returns a path in the temporary directory starting with `bfstool_<name>`, which differs between
+/// processes and calls so tests running in parallel never share it
+fn unique_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!(
+        "bfstool_{}_{}_{}",
+        name,
+        process::id(),
+        NEXT_ID.fetch_add(1, Ordering::Relaxed)
+    ))
+}
+
+/// Archive written to a unique path in the temporary directory, removed when dropped
+pub struct TestArchive {
+    pub path: PathBuf,
+    pub data: Vec<u8>,
+}
+
+impl TestArchive {
+    /// Writes test_data/bfs2004a/europe.bin, which only contains the headers, with made up
+    /// (zeroed) file data appended after them, followed by `trailing`
+    pub fn europe(trailing: &[u8]) -> io::Result<Self> {
+        let mut data = fs::read("test_data/bfs2004a/europe.bin")?;
+        data.resize((EUROPE_DATA_OFFSET + EUROPE_DATA_SIZE) as usize, 0);
+        data.extend_from_slice(trailing);
+
+        let path = unique_path("test").with_extension("bin");
+        fs::write(&path, &data)?;
+
+        Ok(Self { path, data })
+    }
+}
+
+impl Drop for TestArchive {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Empty directory created at a unique path in the temporary directory, removed with everything
+/// in it when dropped
+pub struct TestDir {
+    pub path: PathBuf,
+}
+
+impl TestDir {
+    pub fn new(name: &str) -> io::Result<Self> {
+        let path = unique_path(name);
+        fs::create_dir_all(&path)?;
+
+        Ok(Self { path })
+    }
+
+    /// Returns the path of a file or directory inside the directory
+    pub fn join(&self, path: impl AsRef<Path>) -> PathBuf {
+        self.path.join(path)
+    }
+}
+
+impl Drop for TestDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
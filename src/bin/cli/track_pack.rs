@@ -0,0 +1,136 @@
+use std::error::Error;
+use std::io::Write;
+use std::path::PathBuf;
+
+use clap::Parser;
+
+use bfstool::archive_reader::normalize_separators;
+use bfstool::read_archive_file_with_options;
+
+use crate::config;
+use crate::exit_code::NotFound;
+use crate::progress::file_bar;
+use crate::warning::{check_forced_format, warn_archive};
+
+use super::Format;
+
+#[derive(Parser)]
+pub struct Arguments {
+    /// BFS archive file name
+    archive: PathBuf,
+    /// Ignore invalid magic/version/hash size
+    #[clap(long)]
+    force: bool,
+    /// Track folder inside the archive, e.g. data/tracks/fields/fields1
+    track: String,
+    /// Output directory, taken from the config file if not provided
+    output: Option<PathBuf>,
+    /// BFS archive format, taken from the config file if not provided
+    #[clap(short, long)]
+    format: Option<Format>,
+}
+
+pub fn run(arguments: Arguments, mut writer: impl Write) -> Result<(), Box<dyn Error>> {
+    let format = config::format(arguments.format, &arguments.archive)?;
+    let output = config::output(arguments.output, &arguments.archive)?;
+
+    if arguments.force {
+        check_forced_format(&arguments.archive, format)?;
+    }
+
+    let mut archive = read_archive_file_with_options(
+        &arguments.archive,
+        format,
+        config::read_options(arguments.force),
+    )?;
+    warn_archive(&arguments.archive, archive.as_ref());
+
+    let file_names = track_files(
+        archive.file_names(),
+        &normalize_separators(&arguments.track),
+    );
+    if file_names.is_empty() {
+        return Err(NotFound(arguments.track).into());
+    }
+    let file_count = file_names.len();
+
+    let bar = file_bar(file_count as u64);
+    archive.extract_files(file_names, &output, Box::new(|_, _| bar.inc(1)))?;
+    bar.finish_and_clear();
+
+    writeln!(
+        writer,
+        "Extracted {} for track {}.",
+        if file_count == 1 {
+            "1 file".to_string()
+        } else {
+            format!("{} files", file_count)
+        },
+        arguments.track
+    )?;
+
+    Ok(())
+}
+
+/// Selects all files belonging to the given track folder together with the files it depends on
+///
+/// Tracks are stored as `data/tracks/<environment>/<track>`, e.g. `data/tracks/fields/fields1`.
+/// All files in the environment folder that are not part of another track of the same environment
+/// (e.g. `data/tracks/fields/textures`) are shared between its tracks and selected as well.
+fn track_files(file_names: Vec<String>, track: &str) -> Vec<String> {
+    let track = track.trim_end_matches('/').to_ascii_lowercase();
+    let (environment, _) = track.rsplit_once('/').unwrap_or(("", track.as_str()));
+    let environment_name = environment.rsplit('/').next().unwrap_or_default();
+
+    file_names
+        .into_iter()
+        .filter(|file_name| {
+            let file_name = file_name.to_ascii_lowercase();
+            if file_name.starts_with(&format!("{}/", track)) {
+                return true;
+            }
+            if environment.is_empty() {
+                return false;
+            }
+            match file_name.strip_prefix(&format!("{}/", environment)) {
+                Some(relative_name) => match relative_name.split_once('/') {
+                    Some((folder, _)) => !folder.starts_with(environment_name),
+                    None => true,
+                },
+                None => false,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn track_files_test() {
+        let file_names = [
+            "data/tracks/fields/fields1/a/data/splines.ai",
+            "data/tracks/fields/fields1/a/geometry/track_cdb2.gen",
+            "data/tracks/fields/fields2/a/data/splines.ai",
+            "data/tracks/fields/textures/fence.dds",
+            "data/tracks/fields/menu.bed",
+            "data/tracks/forest/textures/tree.dds",
+            "data/cars/car_1/body.bgm",
+        ]
+        .map(String::from)
+        .to_vec();
+
+        assert_eq!(
+            track_files(file_names, "Data/Tracks/Fields/Fields1/"),
+            vec![
+                "data/tracks/fields/fields1/a/data/splines.ai",
+                "data/tracks/fields/fields1/a/geometry/track_cdb2.gen",
+                "data/tracks/fields/textures/fence.dds",
+                "data/tracks/fields/menu.bed",
+            ]
+        );
+    }
+}
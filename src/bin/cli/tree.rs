@@ -6,9 +6,11 @@ use std::path::PathBuf;
 use clap::Parser;
 use termtree::Tree;
 
-use bfstool::read_archive_file;
+use bfstool::read_archive_file_with_options;
 
+use crate::config;
 use crate::display::display_size;
+use crate::warning::{check_forced_format, warn_archive};
 
 use super::Format;
 
@@ -19,9 +21,13 @@ pub struct Arguments {
     /// Ignore invalid magic/version/hash size
     #[clap(long)]
     force: bool,
-    /// BFS archive format
+    /// BFS archive format, taken from the config file if not provided
     #[clap(short, long)]
-    format: Format,
+    format: Option<Format>,
+    /// Display compressed sizes of the file data stored in the archive, including all copies,
+    /// and their percentage of the archive size instead of unpacked sizes
+    #[clap(long)]
+    physical: bool,
 }
 
 #[derive(Debug, Eq, PartialEq)]
@@ -86,15 +92,24 @@ fn calculate_directory_size(directory: &mut TreeDirectory) {
     directory.size = size;
 }
 
-fn build_printable_tree(directory: &TreeDirectory) -> Tree<String> {
+/// Formats a tree entry, including its percentage of the archive size if given
+fn tree_label(name: &str, size: u64, archive_size: Option<u64>) -> String {
+    match archive_size {
+        Some(archive_size) if archive_size != 0 => format!(
+            "{} [{}, {:.1}%]",
+            name,
+            display_size(&size),
+            size as f64 * 100.0 / archive_size as f64
+        ),
+        _ => format!("{} [{}]", name, display_size(&size)),
+    }
+}
+
+fn build_printable_tree(directory: &TreeDirectory, archive_size: Option<u64>) -> Tree<String> {
     let result = directory.directory_children.iter().fold(
-        Tree::new(format!(
-            "{} [{}]",
-            directory.name,
-            display_size(&directory.size)
-        )),
+        Tree::new(tree_label(&directory.name, directory.size, archive_size)),
         |mut root, directory| {
-            root.push(build_printable_tree(directory));
+            root.push(build_printable_tree(directory, archive_size));
             root
         },
     );
@@ -102,14 +117,25 @@ fn build_printable_tree(directory: &TreeDirectory) -> Tree<String> {
         .file_children
         .iter()
         .fold(result, |mut root, file| {
-            root.push(format!("{} [{}]", file.name, display_size(&file.size)));
+            root.push(tree_label(&file.name, file.size, archive_size));
             root
         });
     result
 }
 
 pub fn run(arguments: Arguments, mut writer: impl std::io::Write) -> Result<(), Box<dyn Error>> {
-    let archive = read_archive_file(&arguments.archive, arguments.format.into(), arguments.force)?;
+    let format = config::format(arguments.format, &arguments.archive)?;
+
+    if arguments.force {
+        check_forced_format(&arguments.archive, format)?;
+    }
+
+    let archive = read_archive_file_with_options(
+        &arguments.archive,
+        format,
+        config::read_options(arguments.force),
+    )?;
+    warn_archive(&arguments.archive, archive.as_ref());
 
     let mut tree = archive
         .multiple_file_info(archive.file_names())
@@ -128,7 +154,12 @@ pub fn run(arguments: Arguments, mut writer: impl std::io::Write) -> Result<(),
             },
             |mut root, (name, file_info)| {
                 let mut path = name.split('/').collect::<VecDeque<&str>>();
-                insert_tree_file(&mut root, &mut path, file_info.size);
+                let size = if arguments.physical {
+                    file_info.compressed_size * (file_info.copies + 1)
+                } else {
+                    file_info.size
+                };
+                insert_tree_file(&mut root, &mut path, size);
                 root
             },
         );
@@ -140,14 +171,15 @@ pub fn run(arguments: Arguments, mut writer: impl std::io::Write) -> Result<(),
         "Listing archive: {}",
         arguments.archive.to_string_lossy()
     )?;
+    let physical_size = fs::metadata(&arguments.archive).unwrap().len();
+    writeln!(writer, "Physical size: {}", display_size(&physical_size))?;
+    writeln!(writer, "File count: {}", archive.file_count())?;
+    writeln!(writer)?;
     writeln!(
         writer,
-        "Physical size: {}",
-        display_size(&fs::metadata(&arguments.archive).unwrap().len())
+        "{}",
+        build_printable_tree(&tree, arguments.physical.then_some(physical_size))
     )?;
-    writeln!(writer, "File count: {}", archive.file_count())?;
-    writeln!(writer)?;
-    writeln!(writer, "{}", build_printable_tree(&tree))?;
 
     Ok(())
 }
@@ -167,7 +199,8 @@ mod tests {
         let arguments = Arguments {
             archive: PathBuf::from("test_data/bfs2004a/europe.bin"),
             force: false,
-            format: Format::Bfs2004a,
+            format: Some(Format::Bfs2004a),
+            physical: false,
         };
         run(arguments, &mut result)?;
 
@@ -191,6 +224,37 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn physical_listing_test() -> Result<(), Box<dyn Error>> {
+        let mut result = Vec::new();
+        let arguments = Arguments {
+            archive: PathBuf::from("test_data/bfs2004a/europe.bin"),
+            force: false,
+            format: Some(Format::Bfs2004a),
+            physical: true,
+        };
+        run(arguments, &mut result)?;
+
+        let mut expected_result_file = File::open("test_data/cli/tree_physical.txt")?;
+        let mut expected_result = Vec::new();
+        expected_result_file.read_to_end(&mut expected_result)?;
+
+        // Compare results as strings for pretty diff when mismatching
+        //
+        // Ignore mismatching line breaks when comparing (assume \r\n and \n are equal) by
+        // removing all occurrences of \r
+        assert_eq!(
+            String::from_utf8_lossy(&result)
+                .to_string()
+                .replace('\r', ""),
+            String::from_utf8_lossy(&expected_result)
+                .to_string()
+                .replace('\r', "")
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn tree_creation_test() {
         let mut tree = TreeDirectory {
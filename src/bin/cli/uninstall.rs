@@ -0,0 +1,35 @@
+use std::error::Error;
+use std::io::Write;
+use std::path::PathBuf;
+
+use clap::Parser;
+
+use crate::exit_code::NotFound;
+use crate::messages::message;
+use crate::safe_write::{uninstall_file, Uninstalled};
+
+#[derive(Parser)]
+pub struct Arguments {
+    /// Path of the installed archive inside the game directory
+    archive: PathBuf,
+}
+
+pub fn run(arguments: Arguments, mut writer: impl Write) -> Result<(), Box<dyn Error>> {
+    let archive = arguments.archive.to_string_lossy();
+    match uninstall_file(&arguments.archive)? {
+        Some(Uninstalled::Restored(backup_path)) => writeln!(
+            writer,
+            "{}",
+            message(
+                "uninstall.restored",
+                &[&archive, &backup_path.to_string_lossy()]
+            )
+        )?,
+        Some(Uninstalled::Removed) => {
+            writeln!(writer, "{}", message("uninstall.removed", &[&archive]))?
+        }
+        None => return Err(NotFound(format!("Installed archive {}", archive)).into()),
+    }
+
+    Ok(())
+}
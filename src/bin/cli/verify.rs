@@ -0,0 +1,273 @@
+use std::error::Error;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+use clap::Parser;
+use tabled::settings::object::{Columns, Segment};
+use tabled::settings::{Alignment, Modify, Style};
+use tabled::{Table, Tabled};
+
+use bfstool::read_archive_file_with_options;
+
+use crate::config;
+use crate::display::{display_crc, display_offset};
+use crate::exit_code::CrcMismatch;
+use crate::fix_crc::stored_data_crc;
+use crate::messages::message;
+use crate::warning::{check_forced_format, warn_archive};
+
+use super::Format;
+
+/// Size of the blocks corrupted data is reported in, matching the size of a CD/DVD ECC block
+const BLOCK_SIZE: u64 = 0x8000;
+
+#[derive(Parser)]
+pub struct Arguments {
+    /// BFS archive file names, all archives have to be of the same format
+    #[clap(required = true)]
+    archives: Vec<PathBuf>,
+    /// Ignore invalid magic/version/hash size
+    #[clap(long)]
+    force: bool,
+    /// BFS archive format, taken from the config file if not provided
+    #[clap(short, long)]
+    format: Option<Format>,
+    /// Amount of archives verified at the same time, defaults to the amount of CPU cores
+    #[clap(short, long)]
+    jobs: Option<usize>,
+}
+
+#[derive(Tabled)]
+struct MismatchingCrc {
+    #[tabled(rename = "Stored CRC", display_with = "display_crc")]
+    stored: u32,
+
+    #[tabled(rename = "Actual CRC", display_with = "display_crc")]
+    actual: u32,
+
+    #[tabled(rename = "Header Block", display_with = "display_offset")]
+    header_block: u64,
+
+    #[tabled(rename = "Data Blocks")]
+    data_blocks: String,
+
+    #[tabled(rename = "Offset", display_with = "display_offset")]
+    offset: u64,
+
+    #[tabled(rename = "File Name")]
+    file_name: String,
+}
+
+/// Result of verifying a single archive
+struct ArchiveReport {
+    checked: usize,
+    mismatching: Vec<MismatchingCrc>,
+}
+
+type VerifyResult = Result<ArchiveReport, Box<dyn Error + Send + Sync>>;
+
+pub fn run(arguments: Arguments, mut writer: impl Write) -> Result<(), Box<dyn Error>> {
+    let format = config::format(arguments.format, &arguments.archives[0])?;
+
+    let jobs = arguments
+        .jobs
+        .or_else(|| thread::available_parallelism().ok().map(usize::from))
+        .unwrap_or(1)
+        .clamp(1, arguments.archives.len());
+    let next_archive = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<VerifyResult>>> =
+        Mutex::new(arguments.archives.iter().map(|_| None).collect());
+    thread::scope(|scope| {
+        for _ in 0..jobs {
+            scope.spawn(|| loop {
+                let index = next_archive.fetch_add(1, Ordering::Relaxed);
+                let Some(archive) = arguments.archives.get(index) else {
+                    break;
+                };
+                let result = verify_archive(archive, format, arguments.force);
+                results.lock().unwrap_or_else(|error| error.into_inner())[index] = Some(result);
+            });
+        }
+    });
+    let results = results
+        .into_inner()
+        .unwrap_or_else(|error| error.into_inner());
+
+    let mut total_mismatching = 0;
+    let mut failed = 0;
+    for (archive, result) in arguments.archives.iter().zip(results) {
+        writeln!(
+            writer,
+            "{}",
+            message("verify.verifying", &[&archive.to_string_lossy()])
+        )?;
+        let report = match result.expect("Every archive is verified") {
+            Ok(report) => report,
+            Err(error) if arguments.archives.len() == 1 => return Err(error),
+            Err(error) => {
+                writeln!(writer, "{}", message("error", &[&error]))?;
+                failed += 1;
+                continue;
+            }
+        };
+        writeln!(writer, "{}", message("verify.checked", &[&report.checked]))?;
+        writeln!(
+            writer,
+            "{}",
+            message("verify.mismatching", &[&report.mismatching.len()])
+        )?;
+        if !report.mismatching.is_empty() {
+            total_mismatching += report.mismatching.len();
+            writeln!(
+                writer,
+                "{}",
+                Table::new(report.mismatching)
+                    .with(Style::markdown())
+                    .with(Modify::new(Segment::all()).with(Alignment::right()))
+                    .with(Modify::new(Columns::last()).with(Alignment::left()))
+            )?;
+        }
+    }
+
+    if arguments.archives.len() > 1 {
+        writeln!(
+            writer,
+            "{}",
+            message("verify.verified_archives", &[&arguments.archives.len()])
+        )?;
+        writeln!(
+            writer,
+            "{}",
+            message("verify.total_mismatching", &[&total_mismatching])
+        )?;
+    }
+
+    if total_mismatching > 0 {
+        Err(CrcMismatch(total_mismatching).into())
+    } else if failed > 0 {
+        Err(message("verify.failed", &[&failed]).into())
+    } else {
+        Ok(())
+    }
+}
+
+/// Checks the CRC32 values of all files in a single archive
+fn verify_archive(archive_path: &Path, format: bfstool::Format, force: bool) -> VerifyResult {
+    if force {
+        check_forced_format(archive_path, format)?;
+    }
+
+    let mut archive = read_archive_file_with_options(
+        &archive_path.to_path_buf(),
+        format,
+        config::read_options(force),
+    )?;
+    warn_archive(archive_path, archive.as_ref());
+
+    let hash_fields = archive.hash_field_offsets();
+    let checked = hash_fields.len();
+
+    let mut mismatching = Vec::new();
+    for (file_name, hash_field_offset, file_info) in hash_fields {
+        let stored = file_info.hash.unwrap_or_default();
+        let actual = stored_data_crc(archive.reader(), &file_name, &file_info)?;
+        if stored != actual {
+            mismatching.push(MismatchingCrc {
+                stored,
+                actual,
+                header_block: hash_field_offset / BLOCK_SIZE * BLOCK_SIZE,
+                data_blocks: display_blocks(file_info.offset, file_info.compressed_size),
+                offset: file_info.offset,
+                file_name,
+            });
+        }
+    }
+
+    Ok(ArchiveReport {
+        checked,
+        mismatching,
+    })
+}
+
+/// Displays the offsets of the first and last block of size [BLOCK_SIZE] the data lies in
+fn display_blocks(offset: u64, size: u64) -> String {
+    let first = offset / BLOCK_SIZE * BLOCK_SIZE;
+    let last = (offset + size.max(1) - 1) / BLOCK_SIZE * BLOCK_SIZE;
+    if first == last {
+        display_offset(&first)
+    } else {
+        format!("{}-{}", display_offset(&first), display_offset(&last))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::test_archive::TestArchive;
+
+    use super::*;
+
+    #[test]
+    fn verify_test() -> Result<(), Box<dyn Error>> {
+        let archive = TestArchive::europe(&[])?;
+
+        let mut result = Vec::new();
+        let arguments = Arguments {
+            archives: vec![archive.path.clone()],
+            force: false,
+            format: Some(Format::Bfs2004a),
+            jobs: None,
+        };
+        let error = run(arguments, &mut result).unwrap_err();
+
+        assert!(error.is::<CrcMismatch>());
+        let result = String::from_utf8_lossy(&result).to_string();
+        assert!(result.contains("Mismatching CRC32 values: 1"));
+        assert!(result
+            .contains("|     00000000 |    00000000 | 00000fdc | data/language/version.ini |"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn verify_header_crc_test() -> Result<(), Box<dyn Error>> {
+        let archive = TestArchive::europe(&[])?;
+
+        // The made up file data is valid, only the CRC stored in the header is corrupted
+        let actual = bfstool::compression::jamcrc(&[0; 0x1D7]);
+        let stored = actual ^ 0x00010000;
+        let mut data = archive.data.clone();
+        data[0xFBC..0xFC0].copy_from_slice(&stored.to_le_bytes());
+        std::fs::write(&archive.path, data)?;
+
+        let mut result = Vec::new();
+        let arguments = Arguments {
+            archives: vec![archive.path.clone()],
+            force: false,
+            format: Some(Format::Bfs2004a),
+            jobs: None,
+        };
+        let error = run(arguments, &mut result).unwrap_err();
+
+        assert!(error.is::<CrcMismatch>());
+        let result = String::from_utf8_lossy(&result).to_string();
+        assert!(result.contains(&format!(
+            "|   {} |   {} |     00000000 |    00000000 | 00000fdc | data/language/version.ini |",
+            display_crc(&stored),
+            display_crc(&actual)
+        )));
+
+        Ok(())
+    }
+
+    #[test]
+    fn display_blocks_test() {
+        assert_eq!(display_blocks(0xFDC, 0x1D7), "00000000");
+        assert_eq!(display_blocks(0x7FFF, 0x2), "00000000-00008000");
+        assert_eq!(display_blocks(0x8000, 0x8000), "00008000");
+    }
+}
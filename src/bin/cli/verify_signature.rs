@@ -0,0 +1,113 @@
+use std::error::Error;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+
+use clap::Parser;
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+
+use crate::sign::{hash_file, signature_path, ArchiveSignature};
+
+#[derive(Parser)]
+pub struct Arguments {
+    /// Archive file name
+    archive: PathBuf,
+    /// Hex-encoded Ed25519 public key the archive is expected to be signed with
+    #[clap(long)]
+    public_key: String,
+    /// Signature file name, `<archive>.sig` if not provided
+    #[clap(long)]
+    signature: Option<PathBuf>,
+}
+
+pub fn run(arguments: Arguments, mut writer: impl Write) -> Result<(), Box<dyn Error>> {
+    let signature_path = arguments
+        .signature
+        .unwrap_or_else(|| signature_path(&arguments.archive));
+    let archive_signature =
+        toml::from_str::<ArchiveSignature>(&fs::read_to_string(signature_path)?)?;
+
+    if !archive_signature
+        .public_key
+        .eq_ignore_ascii_case(arguments.public_key.trim())
+    {
+        return Err("The archive is signed with a different key".into());
+    }
+    let public_key: [u8; 32] = hex::decode(arguments.public_key.trim())?
+        .try_into()
+        .map_err(|_| "The public key has to be 32 bytes long")?;
+    let signature: [u8; 64] = hex::decode(&archive_signature.signature)?
+        .try_into()
+        .map_err(|_| "The signature has to be 64 bytes long")?;
+
+    let hash = hash_file(&arguments.archive)?;
+    if hash.to_hex().as_str() != archive_signature.blake3 {
+        return Err("The archive has been modified after signing".into());
+    }
+
+    VerifyingKey::from_bytes(&public_key)?
+        .verify(hash.as_bytes(), &Signature::from_bytes(&signature))?;
+
+    writeln!(
+        writer,
+        "Signature of {} is valid",
+        arguments.archive.to_string_lossy()
+    )?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use crate::sign;
+    use crate::test_archive::TestDir;
+
+    use super::*;
+
+    #[test]
+    fn sign_and_verify_test() -> Result<(), Box<dyn Error>> {
+        let temp_dir = TestDir::new("sign_test")?;
+        let archive_path = temp_dir.join("europe.bin");
+        let key_path = temp_dir.join("sign.key");
+        fs::copy("test_data/bfs2004a/europe.bin", &archive_path)?;
+
+        let mut sign_output = Vec::new();
+        sign::run(
+            sign::Arguments::try_parse_from([
+                "sign",
+                archive_path.to_str().unwrap(),
+                "--key",
+                key_path.to_str().unwrap(),
+                "--new-key",
+            ])?,
+            &mut sign_output,
+        )?;
+        let public_key = String::from_utf8(sign_output)?
+            .lines()
+            .find_map(|line| line.strip_prefix("Public key: ").map(str::to_string))
+            .unwrap_or_default();
+
+        let verify = |public_key: &str| {
+            run(
+                Arguments {
+                    archive: archive_path.clone(),
+                    public_key: public_key.to_string(),
+                    signature: None,
+                },
+                io::sink(),
+            )
+        };
+        let valid = verify(&public_key);
+        let wrong_key = verify(&"00".repeat(32));
+        fs::write(&archive_path, b"bfs1")?;
+        let modified = verify(&public_key);
+
+        assert!(valid.is_ok());
+        assert!(wrong_key.is_err());
+        assert!(modified.is_err());
+
+        Ok(())
+    }
+}
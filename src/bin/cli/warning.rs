@@ -0,0 +1,45 @@
+use std::io;
+use std::io::{BufRead, BufReader, Seek};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use bfstool::archive_reader::{open_archive_file, ArchiveReader};
+
+use crate::messages;
+
+static WARNING_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// Prints a soft warning and records it, so `--strict` can fail the command afterwards
+pub fn warn(message: impl AsRef<str>) {
+    WARNING_COUNT.fetch_add(1, Ordering::Relaxed);
+    eprintln!("{}", messages::message("warning", &[&message.as_ref()]));
+}
+
+/// Returns how many warnings were emitted so far
+pub fn warning_count() -> usize {
+    WARNING_COUNT.load(Ordering::Relaxed)
+}
+
+/// Warns if the archive does not pass the checks of the given format
+///
+/// Used when the checks are skipped with `--force`
+pub fn check_forced_format(archive: &Path, format: bfstool::Format) -> io::Result<()> {
+    let mut reader = BufReader::new(open_archive_file(archive)?);
+    if let Some(warning) = bfstool::archive_reader::check_forced_format(&mut reader, format) {
+        warn(format!("{}: {}", archive.to_string_lossy(), warning));
+    }
+    Ok(())
+}
+
+/// Warns about every problem found while reading the archive, such as unknown flags or unnamed
+/// files
+///
+/// Called after every archive is read, so `--strict` fails every command reading such an archive
+pub fn warn_archive<R: BufRead + Seek>(
+    archive_path: &Path,
+    archive: &(impl ArchiveReader<R> + ?Sized),
+) {
+    for warning in archive.warnings() {
+        warn(format!("{}: {}", archive_path.to_string_lossy(), warning));
+    }
+}
@@ -0,0 +1,221 @@
+use std::error::Error;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+
+use clap::Parser;
+
+use bfstool::{read_archive_file_with_options, ArchivedFileInfo};
+
+use crate::config;
+use crate::display::{display_offset, display_size};
+use crate::warning::{check_forced_format, warn_archive};
+
+use super::Format;
+
+#[derive(Parser)]
+pub struct Arguments {
+    /// BFS archive file name
+    archive: PathBuf,
+    /// Absolute offset in the archive, in hex with or without a 0x prefix
+    #[clap(value_parser = parse_hex_offset)]
+    offset: u64,
+    /// Ignore invalid magic/version/hash size
+    #[clap(long)]
+    force: bool,
+    /// BFS archive format, taken from the config file if not provided
+    #[clap(short, long)]
+    format: Option<Format>,
+}
+
+pub fn run(arguments: Arguments, mut writer: impl Write) -> Result<(), Box<dyn Error>> {
+    let format = config::format(arguments.format, &arguments.archive)?;
+
+    if arguments.force {
+        check_forced_format(&arguments.archive, format)?;
+    }
+
+    let physical_size = fs::metadata(&arguments.archive)?.len();
+    let archive = read_archive_file_with_options(
+        &arguments.archive,
+        format,
+        config::read_options(arguments.force),
+    )?;
+    warn_archive(&arguments.archive, archive.as_ref());
+
+    let offset = arguments.offset;
+    writeln!(
+        writer,
+        "Looking up offset {} in archive: {}",
+        display_offset(&offset),
+        arguments.archive.to_string_lossy()
+    )?;
+
+    if offset >= physical_size {
+        writeln!(
+            writer,
+            "The offset is past the end of the archive ({})",
+            display_size(&physical_size)
+        )?;
+        return Ok(());
+    }
+    if offset < archive.header_size() {
+        writeln!(writer, "The offset lies in the header section")?;
+        return Ok(());
+    }
+
+    let containing_files =
+        find_containing_files(archive.multiple_file_info(archive.file_names()), offset);
+
+    if containing_files.is_empty() {
+        writeln!(writer, "The offset does not lie in the data of any file")?;
+    }
+    for containing_file in containing_files {
+        writeln!(
+            writer,
+            "{}{} [{} + {} of {}]",
+            containing_file.file_name,
+            if containing_file.copy { " (copy)" } else { "" },
+            display_offset(&containing_file.start),
+            display_offset(&(offset - containing_file.start)),
+            display_size(&containing_file.compressed_size)
+        )?;
+    }
+
+    Ok(())
+}
+
+/// File whose data, or one of its copies, contains the looked up offset
+#[derive(Debug, Eq, PartialEq)]
+struct ContainingFile {
+    file_name: String,
+    /// Offset of the data containing the looked up offset
+    start: u64,
+    /// Whether the data is one of the additional copies of the file
+    copy: bool,
+    compressed_size: u64,
+}
+
+/// Finds all files with data containing the offset
+///
+/// Files sharing the same data all contain the offset, as can a file and its copies
+fn find_containing_files(
+    files: Vec<(String, ArchivedFileInfo)>,
+    offset: u64,
+) -> Vec<ContainingFile> {
+    files
+        .into_iter()
+        .flat_map(|(file_name, file_info)| {
+            let copies = file_info
+                .copies_offsets
+                .iter()
+                .map(|copy_offset| (*copy_offset, true));
+            std::iter::once((file_info.offset, false))
+                .chain(copies)
+                .filter(|(start, _)| *start <= offset && offset < start + file_info.compressed_size)
+                .map(|(start, copy)| ContainingFile {
+                    file_name: file_name.clone(),
+                    start,
+                    copy,
+                    compressed_size: file_info.compressed_size,
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Parses a hex offset, with or without a 0x prefix
+fn parse_hex_offset(value: &str) -> Result<u64, String> {
+    let digits = value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+        .unwrap_or(value);
+    u64::from_str_radix(digits, 16).map_err(|_| format!("{} is not a hex offset", value))
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::test_archive::TestArchive;
+
+    use super::*;
+
+    #[test]
+    fn parse_hex_offset_test() {
+        assert_eq!(parse_hex_offset("0xFDC"), Ok(0xFDC));
+        assert_eq!(parse_hex_offset("fdc"), Ok(0xFDC));
+        assert!(parse_hex_offset("0xFDG").is_err());
+    }
+
+    #[test]
+    fn find_containing_files_test() {
+        let files = vec![
+            (
+                "a.txt".to_string(),
+                ArchivedFileInfo::builder()
+                    .offset(0x100)
+                    .compressed_size(0x10)
+                    .copies(1)
+                    .copies_offsets(vec![0x200])
+                    .build(),
+            ),
+            (
+                "b.txt".to_string(),
+                ArchivedFileInfo::builder()
+                    .offset(0x200)
+                    .compressed_size(0x20)
+                    .build(),
+            ),
+        ];
+
+        assert_eq!(
+            find_containing_files(files.clone(), 0x108),
+            vec![ContainingFile {
+                file_name: "a.txt".to_string(),
+                start: 0x100,
+                copy: false,
+                compressed_size: 0x10,
+            }]
+        );
+        assert_eq!(
+            find_containing_files(files.clone(), 0x208),
+            vec![
+                ContainingFile {
+                    file_name: "a.txt".to_string(),
+                    start: 0x200,
+                    copy: true,
+                    compressed_size: 0x10,
+                },
+                ContainingFile {
+                    file_name: "b.txt".to_string(),
+                    start: 0x200,
+                    copy: false,
+                    compressed_size: 0x20,
+                },
+            ]
+        );
+        assert_eq!(find_containing_files(files, 0x218).len(), 1);
+    }
+
+    #[test]
+    fn whois_offset_test() -> Result<(), Box<dyn Error>> {
+        let archive = TestArchive::europe(&[])?;
+
+        let mut result = Vec::new();
+        let arguments = Arguments {
+            archive: archive.path.clone(),
+            offset: 0x1000,
+            force: false,
+            format: Some(Format::Bfs2004a),
+        };
+        run(arguments, &mut result)?;
+
+        assert_eq!(
+            String::from_utf8_lossy(&result).lines().last(),
+            Some("data/language/version.ini [00000fdc + 00000024 of 471 B]")
+        );
+
+        Ok(())
+    }
+}
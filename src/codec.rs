@@ -0,0 +1,162 @@
+use std::collections::BTreeMap;
+use std::io;
+use std::io::{Read, Write};
+use std::sync::{Arc, RwLock};
+
+use crate::CompressionMethod;
+
+/// Custom compression scheme, e.g. one added by a mod loader
+///
+/// Codecs are registered with [register_codec] for a flag bit. Files of formats supporting custom
+/// codecs with that bit set in their file header flags use
+/// [CompressionMethod::Custom](crate::CompressionMethod::Custom) with that bit.
+///
+/// Implementations should stream the data through a fixed size buffer instead of reading all of
+/// it first, otherwise peak memory use of the extraction depends on the size of the largest file.
+pub trait Codec: Send + Sync {
+    /// Name of the codec, as displayed in place of the compression method
+    fn name(&self) -> &str;
+    /// Decompress everything from `reader` and write the result to `writer`
+    ///
+    /// Returns the amount of bytes written
+    fn decompress(&self, reader: &mut dyn Read, writer: &mut dyn Write) -> io::Result<u64>;
+    /// Compress everything from `reader` and write the result to `writer`
+    ///
+    /// Returns the amount of bytes written
+    fn compress(
+        &self,
+        reader: &mut dyn Read,
+        writer: &mut dyn Write,
+        level: Option<i32>,
+    ) -> io::Result<u64>;
+}
+
+static CODECS: RwLock<BTreeMap<u8, Arc<dyn Codec>>> = RwLock::new(BTreeMap::new());
+
+/// Registers a codec for files with the given flag bit set, replacing any codec registered for
+/// it before
+///
+/// Fails if `flag` does not have exactly one bit set, a flag of 0 would match every file
+pub fn register_codec(flag: u8, codec: impl Codec + 'static) -> io::Result<()> {
+    if flag.count_ones() != 1 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Codec flag {:02X} does not have exactly one bit set", flag),
+        ));
+    }
+    CODECS
+        .write()
+        .unwrap_or_else(|error| error.into_inner())
+        .insert(flag, Arc::new(codec));
+    Ok(())
+}
+
+/// Removes the codec registered for the given flag bit, if any
+pub fn unregister_codec(flag: u8) {
+    CODECS
+        .write()
+        .unwrap_or_else(|error| error.into_inner())
+        .remove(&flag);
+}
+
+/// Returns the codec registered for the given flag bit, if any
+pub fn codec(flag: u8) -> Option<Arc<dyn Codec>> {
+    CODECS
+        .read()
+        .unwrap_or_else(|error| error.into_inner())
+        .get(&flag)
+        .cloned()
+}
+
+/// Returns [CompressionMethod::Custom](crate::CompressionMethod::Custom) for the lowest flag bit
+/// set in `flags` that has a codec registered, if any
+pub(crate) fn registered_method(flags: u8) -> Option<CompressionMethod> {
+    CODECS
+        .read()
+        .unwrap_or_else(|error| error.into_inner())
+        .keys()
+        .find(|flag| flags & **flag == **flag)
+        .map(|flag| CompressionMethod::Custom(*flag))
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::compression::{compress, decompress};
+
+    use super::*;
+
+    /// Codec swapping the case of ASCII letters, streaming the data in small chunks
+    struct SwapCaseCodec;
+
+    impl Codec for SwapCaseCodec {
+        fn name(&self) -> &str {
+            "swapcase"
+        }
+
+        fn decompress(&self, reader: &mut dyn Read, writer: &mut dyn Write) -> io::Result<u64> {
+            self.compress(reader, writer, None)
+        }
+
+        fn compress(
+            &self,
+            reader: &mut dyn Read,
+            writer: &mut dyn Write,
+            _level: Option<i32>,
+        ) -> io::Result<u64> {
+            let mut buffer = [0; 2];
+            let mut written = 0;
+            loop {
+                let read = reader.read(&mut buffer)?;
+                if read == 0 {
+                    return Ok(written);
+                }
+                for byte in &mut buffer[..read] {
+                    if byte.is_ascii_alphabetic() {
+                        *byte ^= 0x20;
+                    }
+                }
+                writer.write_all(&buffer[..read])?;
+                written += read as u64;
+            }
+        }
+    }
+
+    #[test]
+    fn custom_codec_test() -> io::Result<()> {
+        register_codec(0x40, SwapCaseCodec)?;
+        assert!(register_codec(0x00, SwapCaseCodec).is_err());
+        assert!(register_codec(0x60, SwapCaseCodec).is_err());
+
+        assert_eq!(
+            registered_method(0x41),
+            Some(CompressionMethod::Custom(0x40))
+        );
+        assert_eq!(registered_method(0x01), None);
+        assert_eq!(CompressionMethod::Custom(0x40).to_string(), "swapcase");
+
+        let mut compressed = Vec::new();
+        compress(
+            &mut b"bfs1".as_slice(),
+            &mut compressed,
+            CompressionMethod::Custom(0x40),
+            None,
+        )?;
+        assert_eq!(compressed, b"BFS1");
+
+        let mut decompressed = Vec::new();
+        decompress(
+            &mut compressed.as_slice(),
+            &mut decompressed,
+            4,
+            CompressionMethod::Custom(0x40),
+        )?;
+        assert_eq!(decompressed, b"bfs1");
+
+        unregister_codec(0x40);
+        assert_eq!(codec(0x40).map(|codec| codec.name().to_string()), None);
+
+        Ok(())
+    }
+}
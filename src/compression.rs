@@ -1,31 +1,224 @@
 use std::fmt::{Display, Formatter};
 use std::io;
 use std::io::{BufRead, Read, Write};
+use std::sync::Arc;
 
 use flate2::bufread::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
 
-pub fn extract_data<R: BufRead, W: Write>(
+use crate::codec::{codec, Codec};
+
+/// Default size of the chunks data is copied in while decompressing
+pub const DEFAULT_CHUNK_SIZE: usize = 0x8000;
+
+/// Decompress `size` bytes from `reader` using the given method and write the result to `writer`
+///
+/// Returns the amount of bytes written
+pub fn decompress<R: BufRead, W: Write + ?Sized>(
+    reader: &mut R,
+    writer: &mut W,
+    size: u64,
+    method: CompressionMethod,
+) -> io::Result<u64> {
+    decompress_chunked(reader, writer, size, method, DEFAULT_CHUNK_SIZE)
+}
+
+/// Decompress `size` bytes from `reader` like [decompress], writing the result to `writer` in
+/// chunks of at most `chunk_size` bytes
+///
+/// Memory used for the decompressed data does not depend on the size of the file. Custom codecs
+/// are passed the reader and writer directly and are expected to stream the data as well, see
+/// [Codec].
+pub fn decompress_chunked<R: BufRead, W: Write + ?Sized>(
     reader: &mut R,
     writer: &mut W,
     size: u64,
     method: CompressionMethod,
+    chunk_size: usize,
 ) -> io::Result<u64> {
+    trace_span!("decompress", %method, size);
     let mut data = reader.take(size);
     match method {
-        CompressionMethod::None => io::copy(&mut data, writer),
+        CompressionMethod::None => copy_chunked(&mut data, writer, chunk_size),
         CompressionMethod::Zlib => {
             let mut decoder = ZlibDecoder::new(data);
-            io::copy(&mut decoder, writer)
+            copy_chunked(&mut decoder, writer, chunk_size)
         }
         CompressionMethod::Zstd => {
             let mut decoder = zstd::Decoder::new(data)?;
-            io::copy(&mut decoder, writer)
+            copy_chunked(&mut decoder, writer, chunk_size)
+        }
+        CompressionMethod::Custom(flag) => {
+            let mut writer = writer;
+            registered_codec(flag)?.decompress(&mut data, &mut writer)
+        }
+    }
+}
+
+/// Compress everything from `reader` using the given method and write the result to `writer`
+///
+/// If `level` is None, the default level of the method is used. For zlib the level is clamped to
+/// 0-9.
+///
+/// Returns the amount of bytes written
+pub fn compress<R: Read + ?Sized, W: Write>(
+    reader: &mut R,
+    writer: &mut W,
+    method: CompressionMethod,
+    level: Option<i32>,
+) -> io::Result<u64> {
+    trace_span!("compress", %method, ?level);
+    match method {
+        CompressionMethod::None => io::copy(reader, writer),
+        CompressionMethod::Zlib => {
+            let compression = match level {
+                None => Compression::default(),
+                Some(level) => Compression::new(level.clamp(0, 9) as u32),
+            };
+            let mut encoder = ZlibEncoder::new(writer, compression);
+            io::copy(reader, &mut encoder)?;
+            encoder.try_finish()?;
+            Ok(encoder.total_out())
+        }
+        CompressionMethod::Zstd => {
+            let mut counting_writer = CountingWriter { writer, written: 0 };
+            let mut encoder = zstd::Encoder::new(&mut counting_writer, level.unwrap_or(0))?;
+            io::copy(reader, &mut encoder)?;
+            encoder.finish()?;
+            Ok(counting_writer.written)
+        }
+        CompressionMethod::Custom(flag) => {
+            let mut reader = reader;
+            registered_codec(flag)?.compress(&mut reader, writer, level)
         }
     }
 }
 
+/// Returns the codec registered for the given flag, or an error if there is none
+fn registered_codec(flag: u8) -> io::Result<Arc<dyn Codec>> {
+    codec(flag).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("No codec registered for flag {:#04x}", flag),
+        )
+    })
+}
+
+/// Copy everything from `reader` to `writer` through a buffer of `chunk_size` bytes
+///
+/// Returns the amount of bytes written
+fn copy_chunked<R: Read + ?Sized, W: Write + ?Sized>(
+    reader: &mut R,
+    writer: &mut W,
+    chunk_size: usize,
+) -> io::Result<u64> {
+    let mut buffer = vec![0; chunk_size.max(1)];
+    let mut written = 0;
+    loop {
+        let read = match reader.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+            Err(error) => return Err(error),
+        };
+        writer.write_all(&buffer[..read])?;
+        written += read as u64;
+    }
+    Ok(written)
+}
+
+/// Decompress a zlib stream from `reader` until the stream ends, without knowing its size, and
+/// write the result to `writer` in chunks of at most `chunk_size` bytes
+///
+/// Used to recover files whose packed size stored in the file header is damaged. Returns the
+/// compressed and decompressed size of the stream.
+pub fn decompress_zlib_stream<R: BufRead, W: Write + ?Sized>(
+    reader: &mut R,
+    writer: &mut W,
+    chunk_size: usize,
+) -> io::Result<(u64, u64)> {
+    let mut decoder = ZlibDecoder::new(reader);
+    copy_chunked(&mut decoder, writer, chunk_size)?;
+    Ok((decoder.total_in(), decoder.total_out()))
+}
+
+/// Returns the compressed and decompressed size of a zlib stream read from `reader`
+///
+/// See [decompress_zlib_stream]
+pub fn zlib_stream_size<R: BufRead>(reader: &mut R) -> io::Result<(u64, u64)> {
+    decompress_zlib_stream(reader, &mut io::sink(), DEFAULT_CHUNK_SIZE)
+}
+
+/// Calculate the CRC-32/JAMCRC value of the given data, as stored in archive file headers
+pub fn jamcrc(data: &[u8]) -> u32 {
+    !crc32fast::hash(data)
+}
+
+/// Incremental CRC-32/JAMCRC calculation, for data hashed in chunks while streaming it
+///
+/// Data can also be written into it, e.g. with [io::copy]. Partial values of consecutive parts of
+/// the data, for example hashed on different threads, are joined with [JamCrc::combine].
+#[derive(Clone, Default)]
+pub struct JamCrc {
+    hasher: crc32fast::Hasher,
+}
+
+impl JamCrc {
+    /// Creates a new JamCrc without any data hashed
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Hashes the next chunk of data
+    pub fn update(&mut self, data: &[u8]) {
+        self.hasher.update(data);
+    }
+
+    /// Appends the data hashed by `other` to the data hashed so far, as if it was passed to
+    /// [JamCrc::update] after it
+    pub fn combine(&mut self, other: &Self) {
+        self.hasher.combine(&other.hasher);
+    }
+
+    /// Returns the CRC-32/JAMCRC value of all hashed data
+    pub fn finalize(self) -> u32 {
+        !self.hasher.finalize()
+    }
+}
+
+impl Write for JamCrc {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Writer keeping track of the amount of bytes written into the inner writer
+struct CountingWriter<'a, W: Write> {
+    writer: &'a mut W,
+    written: u64,
+}
+
+impl<W: Write> Write for CountingWriter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.writer.write(buf)?;
+        self.written += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
 /// Available compression methods
-#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Default, Eq, Hash, PartialEq)]
+#[non_exhaustive]
 pub enum CompressionMethod {
     /// No compression
     #[default]
@@ -34,18 +227,131 @@ pub enum CompressionMethod {
     Zlib,
     /// Zstandard compression
     Zstd,
+    /// Compression with the [Codec] registered for the given flag bit
+    Custom(u8),
 }
 
 impl Display for CompressionMethod {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{}",
-            match self {
-                CompressionMethod::None => "none",
-                CompressionMethod::Zlib => "zlib",
-                CompressionMethod::Zstd => "zstd",
-            }
-        )
+        match self {
+            CompressionMethod::None => write!(f, "none"),
+            CompressionMethod::Zlib => write!(f, "zlib"),
+            CompressionMethod::Zstd => write!(f, "zstd"),
+            CompressionMethod::Custom(flag) => match codec(*flag) {
+                Some(codec) => write!(f, "{}", codec.name()),
+                None => write!(f, "custom ({:#04x})", flag),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn round_trip_test() -> io::Result<()> {
+        let data = b"data/language/version.ini ".repeat(64);
+
+        for method in [
+            CompressionMethod::None,
+            CompressionMethod::Zlib,
+            CompressionMethod::Zstd,
+        ] {
+            let mut compressed = Vec::new();
+            let compressed_size = compress(&mut data.as_slice(), &mut compressed, method, None)?;
+            assert_eq!(compressed_size, compressed.len() as u64);
+
+            let mut decompressed = Vec::new();
+            let decompressed_size = decompress(
+                &mut Cursor::new(&compressed),
+                &mut decompressed,
+                compressed_size,
+                method,
+            )?;
+            assert_eq!(decompressed_size, data.len() as u64);
+            assert_eq!(decompressed, data);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn chunked_test() -> io::Result<()> {
+        let data = b"data/language/version.ini ".repeat(64);
+        let mut compressed = Vec::new();
+        let compressed_size = compress(
+            &mut data.as_slice(),
+            &mut compressed,
+            CompressionMethod::Zlib,
+            None,
+        )?;
+
+        for chunk_size in [0, 1, 7, DEFAULT_CHUNK_SIZE] {
+            let mut decompressed = Vec::new();
+            let decompressed_size = decompress_chunked(
+                &mut Cursor::new(&compressed),
+                &mut decompressed,
+                compressed_size,
+                CompressionMethod::Zlib,
+                chunk_size,
+            )?;
+            assert_eq!(decompressed_size, data.len() as u64);
+            assert_eq!(decompressed, data);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn zlib_stream_size_test() -> io::Result<()> {
+        let data = b"data/language/version.ini ".repeat(64);
+        let mut compressed = Vec::new();
+        compress(
+            &mut data.as_slice(),
+            &mut compressed,
+            CompressionMethod::Zlib,
+            None,
+        )?;
+        let compressed_size = compressed.len() as u64;
+        // Data of the next file follows the stream
+        compressed.extend_from_slice(&[0xAA; 0x100]);
+
+        assert_eq!(
+            zlib_stream_size(&mut Cursor::new(&compressed))?,
+            (compressed_size, data.len() as u64)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn jamcrc_test() {
+        assert_eq!(jamcrc(b"123456789"), 0x340BC6D9);
+    }
+
+    #[test]
+    fn jamcrc_chunks_test() -> io::Result<()> {
+        let mut chunked = JamCrc::new();
+        chunked.update(b"1234");
+        chunked.update(b"56789");
+        assert_eq!(chunked.finalize(), 0x340BC6D9);
+
+        let mut first = JamCrc::new();
+        first.update(b"1234");
+        let mut second = JamCrc::new();
+        second.update(b"56789");
+        first.combine(&second);
+        assert_eq!(first.finalize(), 0x340BC6D9);
+
+        let mut written = JamCrc::new();
+        io::copy(&mut b"123456789".as_slice(), &mut written)?;
+        assert_eq!(written.finalize(), 0x340BC6D9);
+
+        Ok(())
     }
 }
@@ -0,0 +1,91 @@
+use std::collections::BTreeMap;
+use std::io;
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+
+/// Post-extract conversion of extracted files, e.g. of console texture containers (`.tm2` on PS2,
+/// swizzled `.dds` on Xbox) to formats usable on PC
+///
+/// Converters are registered with [register_converter] for a file extension. Files with that
+/// extension are passed to the converter after being extracted with
+/// [ExtractOptions::convert](crate::archive_reader::ExtractOptions::convert) set.
+pub trait Converter: Send + Sync {
+    /// Name of the converter
+    fn name(&self) -> &str;
+    /// Convert the extracted file at `path`, either in place or by writing converted files next to
+    /// it
+    fn convert(&self, path: &Path) -> io::Result<()>;
+}
+
+/// Converter leaving extracted files untouched
+///
+/// Can be registered for an extension to disable the conversion of files with that extension
+pub struct NoopConverter;
+
+impl Converter for NoopConverter {
+    fn name(&self) -> &str {
+        "none"
+    }
+
+    fn convert(&self, _path: &Path) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+static CONVERTERS: RwLock<BTreeMap<String, Arc<dyn Converter>>> = RwLock::new(BTreeMap::new());
+
+/// Registers a converter for files with the given extension, replacing any converter registered
+/// for it before
+///
+/// Extensions are given without the leading dot and matched case-insensitively
+pub fn register_converter(extension: &str, converter: impl Converter + 'static) {
+    CONVERTERS
+        .write()
+        .unwrap_or_else(|error| error.into_inner())
+        .insert(extension.to_ascii_lowercase(), Arc::new(converter));
+}
+
+/// Removes the converter registered for the given extension, if any
+pub fn unregister_converter(extension: &str) {
+    CONVERTERS
+        .write()
+        .unwrap_or_else(|error| error.into_inner())
+        .remove(&extension.to_ascii_lowercase());
+}
+
+/// Returns the converter registered for the extension of the given path, if any
+pub fn converter(path: &Path) -> Option<Arc<dyn Converter>> {
+    let extension = path.extension()?.to_string_lossy().to_ascii_lowercase();
+    CONVERTERS
+        .read()
+        .unwrap_or_else(|error| error.into_inner())
+        .get(&extension)
+        .cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn converter_registry_test() {
+        register_converter("TM2", NoopConverter);
+
+        assert_eq!(
+            converter(Path::new("data/menu/bg.tm2")).map(|converter| converter.name().to_string()),
+            Some("none".to_string())
+        );
+        assert_eq!(
+            converter(Path::new("data/menu/bg.dds")).map(|converter| converter.name().to_string()),
+            None
+        );
+
+        unregister_converter("tm2");
+        assert_eq!(
+            converter(Path::new("data/menu/bg.tm2")).map(|converter| converter.name().to_string()),
+            None
+        );
+    }
+}
@@ -7,3 +7,42 @@ mod encrypt;
 /// Key used in bzf2001 encryption
 pub type Key = [u8; 256];
 
+/// Applies `key` to `data` in place, starting at `key_position` in the key
+///
+/// Bzf2001 encryption XORs every byte with the next byte of the key, wrapping around after 256
+/// bytes, so the same operation encrypts and decrypts. The file headers are processed as a single
+/// block starting at key position 0, the data of every file restarts at key position 0 as well.
+///
+/// Returns the key position following the data
+pub fn apply_key(data: &mut [u8], key: &Key, key_position: usize) -> usize {
+    let mut key_position = key_position % key.len();
+    data.iter_mut().for_each(|value| {
+        *value ^= key[key_position];
+        key_position += 1;
+        if key_position == key.len() {
+            key_position = 0;
+        }
+    });
+    key_position
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn apply_key_test() {
+        let mut key = [0; 256];
+        key.iter_mut()
+            .enumerate()
+            .for_each(|(index, value)| *value = index as u8);
+
+        let mut data = [0xFF; 4];
+        assert_eq!(apply_key(&mut data, &key, 254), 2);
+        assert_eq!(data, [0x01, 0x00, 0xFF, 0xFE]);
+        assert_eq!(apply_key(&mut data, &key, 254), 2);
+        assert_eq!(data, [0xFF; 4]);
+    }
+}
@@ -5,7 +5,7 @@ use std::path::PathBuf;
 
 use binrw::BinRead;
 
-use crate::crypt::bzf2001::Key;
+use crate::crypt::bzf2001::{apply_key, Key};
 use crate::crypt::CryptError;
 use crate::formats::bzf2001::{ArchiveHeader, FileHeader};
 
@@ -26,14 +26,7 @@ pub fn decrypt<R: BufRead + Seek + 'static, W: Write + Seek + 'static>(
     let file_headers_size = archive_header.file_count * 0x35; // 0x35 - Size of the physical representation of a FileHeader
     let mut file_headers_data = vec![0; file_headers_size as usize];
     input.read_exact(&mut file_headers_data)?;
-    let mut key_position = 0;
-    file_headers_data.iter_mut().for_each(|value| {
-        *value ^= key[key_position];
-        key_position += 1;
-        if key_position == 256 {
-            key_position = 0;
-        }
-    });
+    apply_key(&mut file_headers_data, &key, 0);
     output.write_all(&file_headers_data)?;
 
     let mut file_headers_data = Cursor::new(file_headers_data);
@@ -47,7 +40,7 @@ pub fn decrypt<R: BufRead + Seek + 'static, W: Write + Seek + 'static>(
         .collect::<Vec<u32>>();
     let mut offset = input.stream_position()? as u32;
     let mut key_resets = 1;
-    key_position = 0;
+    let mut key_position = 0;
 
     let mut buffer = [0; 4096];
     loop {
@@ -5,7 +5,7 @@ use std::path::PathBuf;
 
 use binrw::BinRead;
 
-use crate::crypt::bzf2001::Key;
+use crate::crypt::bzf2001::{apply_key, Key};
 use crate::crypt::CryptError;
 use crate::formats::bzf2001::{ArchiveHeader, FileHeader};
 
@@ -32,14 +32,7 @@ pub fn encrypt<R: BufRead + Seek + 'static, W: Write + Seek + 'static>(
         .map(|_| FileHeader::read(&mut file_headers_data_cursor))
         .collect::<Result<Vec<FileHeader>, _>>()?;
 
-    let mut key_position = 0;
-    file_headers_data.iter_mut().for_each(|value| {
-        *value ^= key[key_position];
-        key_position += 1;
-        if key_position == 256 {
-            key_position = 0;
-        }
-    });
+    apply_key(&mut file_headers_data, &key, 0);
     output.write_all(&file_headers_data)?;
 
     let key_reset_offsets = file_headers
@@ -48,7 +41,7 @@ pub fn encrypt<R: BufRead + Seek + 'static, W: Write + Seek + 'static>(
         .collect::<Vec<u32>>();
     let mut offset = input.stream_position()? as u32;
     let mut key_resets = 1;
-    key_position = 0;
+    let mut key_position = 0;
 
     let mut buffer = [0; 4096];
     loop {
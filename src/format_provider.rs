@@ -0,0 +1,149 @@
+use std::collections::BTreeMap;
+use std::io::{BufRead, Seek};
+use std::sync::{Arc, RwLock};
+
+use crate::archive_reader::{ArchiveReader, ReadError};
+
+/// Reader of an archive read by a [FormatProvider]
+pub trait ProviderSource: BufRead + Seek {}
+
+impl<T: BufRead + Seek> ProviderSource for T {}
+
+/// Implementation of an archive format outside of bfstool, e.g. of a community-researched variant
+///
+/// Providers are registered with [register_format_provider] under their name, which then can be
+/// used to read archives with [read_archive_with_provider], to look them up with
+/// [format_provider], or to detect the format of an archive with [probe_format_providers].
+/// Built-in formats are not affected and are still read with [read_archive](crate::read_archive).
+///
+/// Changes to this trait are considered breaking changes of bfstool.
+pub trait FormatProvider: Send + Sync {
+    /// Name of the format, e.g. `bfs2011`
+    fn name(&self) -> &str;
+    /// Checks whether the archive is of this format, without reading the whole archive
+    fn probe(&self, archive: &mut dyn ProviderSource) -> bool;
+    /// Reads the archive
+    ///
+    /// If `force` is true then format checks should be skipped where possible
+    fn read(
+        &self,
+        archive: Box<dyn ProviderSource>,
+        force: bool,
+    ) -> Result<Box<dyn ArchiveReader<Box<dyn ProviderSource>>>, ReadError>;
+}
+
+static PROVIDERS: RwLock<BTreeMap<String, Arc<dyn FormatProvider>>> = RwLock::new(BTreeMap::new());
+
+/// Registers a format provider under its name, replacing any provider registered with the same
+/// name before
+///
+/// Names are matched case-insensitively
+pub fn register_format_provider(provider: impl FormatProvider + 'static) {
+    PROVIDERS
+        .write()
+        .unwrap_or_else(|error| error.into_inner())
+        .insert(provider.name().to_ascii_lowercase(), Arc::new(provider));
+}
+
+/// Removes the format provider registered under the given name, if any
+pub fn unregister_format_provider(name: &str) {
+    PROVIDERS
+        .write()
+        .unwrap_or_else(|error| error.into_inner())
+        .remove(&name.to_ascii_lowercase());
+}
+
+/// Returns the format provider registered under the given name, if any
+pub fn format_provider(name: &str) -> Option<Arc<dyn FormatProvider>> {
+    PROVIDERS
+        .read()
+        .unwrap_or_else(|error| error.into_inner())
+        .get(&name.to_ascii_lowercase())
+        .cloned()
+}
+
+/// Reads the archive with the format provider registered under the given name
+///
+/// If `force` is true then format checks should be skipped where possible
+pub fn read_archive_with_provider(
+    archive: Box<dyn ProviderSource>,
+    name: &str,
+    force: bool,
+) -> Result<Box<dyn ArchiveReader<Box<dyn ProviderSource>>>, ReadError> {
+    let provider = format_provider(name).ok_or_else(|| ReadError::NoFormatProvider {
+        name: name.to_string(),
+    })?;
+    provider.read(archive, force)
+}
+
+/// Returns the first registered format provider, in name order, detecting the archive as its
+/// format
+pub fn probe_format_providers(archive: &mut dyn ProviderSource) -> Option<Arc<dyn FormatProvider>> {
+    let providers = PROVIDERS
+        .read()
+        .unwrap_or_else(|error| error.into_inner())
+        .values()
+        .cloned()
+        .collect::<Vec<_>>();
+    providers.into_iter().find(|provider| {
+        let detected = provider.probe(archive);
+        let _ = archive.rewind();
+        detected
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+    use std::io::BufReader;
+
+    use pretty_assertions::assert_eq;
+
+    use crate::formats::bzf2002;
+    use crate::{read_archive, Format};
+
+    use super::*;
+
+    /// Provider reading Bzf2002 archives under a different name
+    struct TestProvider;
+
+    impl FormatProvider for TestProvider {
+        fn name(&self) -> &str {
+            "Test"
+        }
+
+        fn probe(&self, mut archive: &mut dyn ProviderSource) -> bool {
+            bzf2002::check_archive(&mut archive).is_ok()
+        }
+
+        fn read(
+            &self,
+            archive: Box<dyn ProviderSource>,
+            force: bool,
+        ) -> Result<Box<dyn ArchiveReader<Box<dyn ProviderSource>>>, ReadError> {
+            read_archive(archive, Format::Bzf2002, force)
+        }
+    }
+
+    #[test]
+    fn format_provider_test() -> Result<(), ReadError> {
+        register_format_provider(TestProvider);
+
+        let mut archive = BufReader::new(File::open("test_data/bzf2002/tt_Language.bin")?);
+        let provider = probe_format_providers(&mut archive).expect("Archive is not detected");
+        assert_eq!(provider.name(), "Test");
+
+        let archive = read_archive_with_provider(Box::new(archive), "test", false)?;
+        assert!(archive.file_count() > 0);
+
+        unregister_format_provider("TEST");
+        assert!(format_provider("test").is_none());
+        let archive = BufReader::new(File::open("test_data/bzf2002/tt_Language.bin")?);
+        assert!(matches!(
+            read_archive_with_provider(Box::new(archive), "test", false),
+            Err(ReadError::NoFormatProvider { .. })
+        ));
+
+        Ok(())
+    }
+}
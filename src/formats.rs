@@ -1,3 +1,5 @@
+use std::fmt::{Display, Formatter};
+
 /// Support for the Bfs2004a archive format
 pub mod bfs2004a;
 /// Support for the Bfs2004b archive format
@@ -8,8 +10,11 @@ pub mod bfs2007;
 pub mod bzf2001;
 /// Support for the Bzf2002 archive format
 pub mod bzf2002;
+/// Best-effort reading of the Bfs2011 and Bfs2013 archive formats, which are not understood yet
+pub mod partial;
 
 /// Available archive formats to use
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
 pub enum Format {
     /// `bbzf` v2001.06.06 format
     ///
@@ -52,3 +57,63 @@ pub enum Format {
     /// - Next Car Game Technology Sneak Peek 2.0
     Bfs2013,
 }
+
+impl Display for Format {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Format::Bzf2001 => "bzf2001",
+                Format::Bzf2002 => "bzf2002",
+                Format::Bfs2004a => "bfs2004a",
+                Format::Bfs2004b => "bfs2004b",
+                Format::Bfs2007 => "bfs2007",
+                Format::Bfs2011 => "bfs2011",
+                Format::Bfs2013 => "bfs2013",
+            }
+        )
+    }
+}
+
+/// Formats a file version stored in an archive header as a date, e.g. `0x20040505` as
+/// `2004.05.05`
+pub fn format_version(version: u32) -> String {
+    format!(
+        "{:04x}.{:02x}.{:02x}",
+        version >> 16,
+        (version >> 8) & 0xFF,
+        version & 0xFF
+    )
+}
+
+/// Parses a file version formatted by [format_version], e.g. `2004.05.05` as `0x20040505`
+pub fn parse_version(version: &str) -> Option<u32> {
+    let parts = version.split('.').collect::<Vec<&str>>();
+    if parts.len() != 3
+        || parts[0].len() != 4
+        || parts[1].len() != 2
+        || parts[2].len() != 2
+        || !parts
+            .iter()
+            .all(|part| part.bytes().all(|byte| byte.is_ascii_digit()))
+    {
+        return None;
+    }
+    u32::from_str_radix(&parts.concat(), 16).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn version_test() {
+        assert_eq!(format_version(bfs2007::VERSION), "2007.03.10");
+        assert_eq!(parse_version("2004.05.05"), Some(bfs2004a::VERSION));
+        assert_eq!(parse_version("2004.5.5"), None);
+        assert_eq!(parse_version("2004.05.0a"), None);
+    }
+}
@@ -43,6 +43,10 @@ impl<R: BufRead + Seek> ArchiveReader<R> for ReadArchive<R> {
         self.raw_archive.archive_header.file_count as u64
     }
 
+    fn header_size(&self) -> u64 {
+        self.raw_archive.archive_header.header_end as u64
+    }
+
     fn file_names(&self) -> Vec<String> {
         self.raw_archive
             .file_headers
@@ -82,9 +86,74 @@ impl<R: BufRead + Seek> ArchiveReader<R> for ReadArchive<R> {
             .collect()
     }
 
+    fn unknown_flags(&self) -> Vec<(String, u8)> {
+        self.raw_archive
+            .file_headers
+            .iter()
+            .filter(|file_header| file_header.unknown_flags() != 0)
+            .map(|file_header| (file_header.file_name.clone(), file_header.flags))
+            .collect()
+    }
+
+    fn file_flags(&self) -> Vec<(String, u8)> {
+        self.raw_archive
+            .file_headers
+            .iter()
+            .map(|file_header| (file_header.file_name.clone(), file_header.flags))
+            .collect()
+    }
+
     fn reader(&mut self) -> &mut R {
         &mut self.reader
     }
+
+    fn hash_field_offsets(&self) -> Vec<(String, u64, ArchivedFileInfo)> {
+        // File headers are stored right after the hash table
+        let mut file_header_offset = 0x10
+            + self.raw_archive.archive_header.file_count as u64 * 4
+            + 4
+            + self.raw_archive.hash_table.hash_size as u64 * 4;
+        self.raw_archive
+            .file_headers
+            .iter()
+            .filter_map(|file_header| {
+                let hash_field_offset = file_header_offset + 0x10;
+                file_header_offset +=
+                    0x16 + file_header.file_name_length as u64 + file_header.file_copies as u64 * 4;
+                if file_header.flags & 0x04 == 0x04 {
+                    Some((
+                        file_header.file_name.clone(),
+                        hash_field_offset,
+                        ArchivedFileInfo::from(file_header),
+                    ))
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    fn size_field_offsets(&self) -> Vec<(String, u64, ArchivedFileInfo)> {
+        // File headers are stored right after the hash table
+        let mut file_header_offset = 0x10
+            + self.raw_archive.archive_header.file_count as u64 * 4
+            + 4
+            + self.raw_archive.hash_table.hash_size as u64 * 4;
+        self.raw_archive
+            .file_headers
+            .iter()
+            .map(|file_header| {
+                let size_field_offset = file_header_offset + 0xC;
+                file_header_offset +=
+                    0x16 + file_header.file_name_length as u64 + file_header.file_copies as u64 * 4;
+                (
+                    file_header.file_name.clone(),
+                    size_field_offset,
+                    ArchivedFileInfo::from(file_header),
+                )
+            })
+            .collect()
+    }
 }
 
 /// Checks the magic, version and hash size of the archive to ensure it's a valid Bfs2004a archive
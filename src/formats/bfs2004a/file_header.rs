@@ -1,11 +1,14 @@
 use binrw::BinRead;
 
+use crate::codec::registered_method;
+use crate::name_encoding::NameEncoding;
 use crate::ArchivedFileInfo;
 use crate::CompressionMethod;
 
 /// Header for a single file in a Bfs2004a archive
 #[derive(Debug, Default, Eq, PartialEq, BinRead)]
 #[brw(little)]
+#[br(import(name_encoding: NameEncoding))]
 pub struct FileHeader {
     /// Flags for the archived file
     ///
@@ -38,25 +41,42 @@ pub struct FileHeader {
     /// In official archives, file name length can not be 0. If reading an unofficial archive and
     /// the file name length is 0, the file name will be empty and that case needs to be handled
     /// in the user's code
-    #[br(count = file_name_length, map = |bytes: Vec<u8>| { String::from_utf8_lossy(&bytes).to_string() })]
+    #[br(count = file_name_length, map = |bytes: Vec<u8>| { name_encoding.decode(&bytes) })]
     pub file_name: String,
     /// Absolute offsets of all additional file copies
     #[br(count = file_copies)]
     pub file_copies_offsets: Vec<u32>,
 }
 
+impl FileHeader {
+    /// Returns the flags set on the file which bfstool does not know the meaning of, all flags
+    /// except `0x01` and `0x04` and the flag of a registered codec
+    pub fn unknown_flags(&self) -> u8 {
+        let mut unknown_flags = self.flags & !0x05;
+        if let CompressionMethod::Custom(flag) = ArchivedFileInfo::from(self).compression_method {
+            unknown_flags &= !flag;
+        }
+        unknown_flags
+    }
+}
+
 impl From<&FileHeader> for ArchivedFileInfo {
     fn from(file_header: &FileHeader) -> Self {
         Self {
             offset: file_header.data_offset as u64,
             compression_method: if file_header.flags & 0x01 == 0x01 {
-                CompressionMethod::Zlib
+                registered_method(file_header.flags & !0x05).unwrap_or(CompressionMethod::Zlib)
             } else {
                 CompressionMethod::None
             },
             size: file_header.unpacked_size as u64,
             compressed_size: file_header.packed_size as u64,
             copies: file_header.file_copies as u64,
+            copies_offsets: file_header
+                .file_copies_offsets
+                .iter()
+                .map(|offset| *offset as u64)
+                .collect(),
             hash: if file_header.flags & 0x04 == 0x04 {
                 Some(file_header.crc32)
             } else {
@@ -135,8 +155,13 @@ mod tests {
         let result = FileHeader::read(&mut test_reader);
 
         assert!(result.is_ok());
+        let file_header = result.unwrap();
         assert_eq!(
-            result.unwrap(),
+            ArchivedFileInfo::from(&file_header).copies_offsets,
+            vec![0xD4DD3E4]
+        );
+        assert_eq!(
+            file_header,
             FileHeader {
                 flags: 0x01,
                 file_copies: 1,
@@ -180,4 +205,19 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn unknown_flags_test() {
+        let file_header = FileHeader {
+            flags: 0x07,
+            ..Default::default()
+        };
+        assert_eq!(file_header.unknown_flags(), 0x02);
+
+        let file_header = FileHeader {
+            flags: 0x05,
+            ..Default::default()
+        };
+        assert_eq!(file_header.unknown_flags(), 0x00);
+    }
 }
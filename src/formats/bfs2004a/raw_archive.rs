@@ -1,10 +1,12 @@
 use binrw::BinRead;
 
 use crate::formats::bfs2004a::{ArchiveHeader, FileHeader, FileHeaderOffsetTable, HashTable};
+use crate::name_encoding::NameEncoding;
 
 /// Raw archive contents that can be read directly from a .bfs file or written to one
 #[derive(Debug, Default, Eq, PartialEq, BinRead)]
 #[brw(little)]
+#[br(import(name_encoding: NameEncoding))]
 pub struct RawArchive {
     /// The archive header
     pub archive_header: ArchiveHeader,
@@ -14,7 +16,7 @@ pub struct RawArchive {
     /// Stores information about the hash size and how many files with specific hash are there
     pub hash_table: HashTable,
     /// All [FileHeader]s
-    #[br(count = archive_header.file_count)]
+    #[br(args { count: archive_header.file_count as usize, inner: (name_encoding,) })]
     pub file_headers: Vec<FileHeader>,
 }
 
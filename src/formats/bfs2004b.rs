@@ -7,8 +7,9 @@ pub use file_header::FileHeader;
 pub use hash_table::HashTable;
 pub use hash_table_entry::HashTableEntry;
 pub use huffman_dict_entry::{HuffmanDictEntry, HuffmanDictNodeType};
-pub use huffman_helpers::decode_all_names;
+pub use huffman_helpers::{decode_all_names, decode_all_names_bytes};
 pub use metadata_header::MetadataHeader;
+pub use name_table::{NameTable, NameTableProblem};
 pub use raw_archive::RawArchive;
 
 use crate::archive_reader::ReadError::{InvalidHashSize, InvalidMagic, InvalidVersion};
@@ -24,6 +25,7 @@ mod huffman_helpers;
 mod metadata_header;
 /// Utilities to help deserialize metadata
 pub mod metadata_helpers;
+mod name_table;
 mod raw_archive;
 
 /// Amount of entries in the hash table
@@ -99,6 +101,10 @@ impl<R: BufRead + Seek> ArchiveReader<R> for ReadArchive<R> {
         self.raw_archive.archive_header.file_count as u64
     }
 
+    fn header_size(&self) -> u64 {
+        self.raw_archive.archive_header.header_end as u64
+    }
+
     fn file_names(&self) -> Vec<String> {
         self.raw_archive
             .file_headers
@@ -136,7 +142,66 @@ impl<R: BufRead + Seek> ArchiveReader<R> for ReadArchive<R> {
             .collect()
     }
 
+    fn unknown_flags(&self) -> Vec<(String, u8)> {
+        self.raw_archive
+            .file_headers
+            .iter()
+            .filter(|file_header| file_header.unknown_flags() != 0)
+            .map(|file_header| (self.file_header_to_name(file_header), file_header.flags))
+            .collect()
+    }
+
+    fn file_flags(&self) -> Vec<(String, u8)> {
+        self.raw_archive
+            .file_headers
+            .iter()
+            .map(|file_header| (self.file_header_to_name(file_header), file_header.flags))
+            .collect()
+    }
+
     fn reader(&mut self) -> &mut R {
         &mut self.reader
     }
+
+    fn hash_field_offsets(&self) -> Vec<(String, u64, ArchivedFileInfo)> {
+        let mut file_header_offset =
+            metadata_helpers::calculate_metadata_start(&self.raw_archive.hash_table) as u64
+                + self.raw_archive.metadata_header.file_headers_offset as u64;
+        self.raw_archive
+            .file_headers
+            .iter()
+            .filter_map(|file_header| {
+                let hash_field_offset = file_header_offset + 0x10;
+                file_header_offset += 0x18 + file_header.file_copies as u64 * 4;
+                if file_header.flags & 0x04 == 0x04 {
+                    Some((
+                        self.file_header_to_name(file_header),
+                        hash_field_offset,
+                        ArchivedFileInfo::from(file_header),
+                    ))
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    fn size_field_offsets(&self) -> Vec<(String, u64, ArchivedFileInfo)> {
+        let mut file_header_offset =
+            metadata_helpers::calculate_metadata_start(&self.raw_archive.hash_table) as u64
+                + self.raw_archive.metadata_header.file_headers_offset as u64;
+        self.raw_archive
+            .file_headers
+            .iter()
+            .map(|file_header| {
+                let size_field_offset = file_header_offset + 0xC;
+                file_header_offset += 0x18 + file_header.file_copies as u64 * 4;
+                (
+                    self.file_header_to_name(file_header),
+                    size_field_offset,
+                    ArchivedFileInfo::from(file_header),
+                )
+            })
+            .collect()
+    }
 }
@@ -1,5 +1,6 @@
 use binrw::BinRead;
 
+use crate::codec::registered_method;
 use crate::ArchivedFileInfo;
 use crate::CompressionMethod;
 
@@ -39,6 +40,18 @@ pub struct FileHeader {
     pub file_copies_offsets: Vec<u32>,
 }
 
+impl FileHeader {
+    /// Returns the flags set on the file which bfstool does not know the meaning of, all flags
+    /// except `0x01`, `0x04` and `0x08` and the flag of a registered codec
+    pub fn unknown_flags(&self) -> u8 {
+        let mut unknown_flags = self.flags & !0x0D;
+        if let CompressionMethod::Custom(flag) = ArchivedFileInfo::from(self).compression_method {
+            unknown_flags &= !flag;
+        }
+        unknown_flags
+    }
+}
+
 impl From<&FileHeader> for ArchivedFileInfo {
     fn from(file_header: &FileHeader) -> Self {
         Self {
@@ -47,7 +60,7 @@ impl From<&FileHeader> for ArchivedFileInfo {
                 if file_header.flags & 0x08 == 0x08 {
                     CompressionMethod::Zstd
                 } else {
-                    CompressionMethod::Zlib
+                    registered_method(file_header.flags & !0x0D).unwrap_or(CompressionMethod::Zlib)
                 }
             } else {
                 CompressionMethod::None
@@ -55,6 +68,11 @@ impl From<&FileHeader> for ArchivedFileInfo {
             size: file_header.unpacked_size as u64,
             compressed_size: file_header.packed_size as u64,
             copies: file_header.file_copies as u64,
+            copies_offsets: file_header
+                .file_copies_offsets
+                .iter()
+                .map(|offset| *offset as u64)
+                .collect(),
             hash: if file_header.flags & 0x04 == 0x04 {
                 Some(file_header.crc32)
             } else {
@@ -6,17 +6,41 @@ use crate::formats::bfs2004b::{
     EncodedHuffmanData, FileNameLengthTable, FileNameOffsetTable, HuffmanDictNodeType,
     SerializedHuffmanDict,
 };
+use crate::name_encoding::NameEncoding;
 
 /// Contains the deserialized Huffman dictionary
 type HuffmanDict = HashMap<u32, u8>;
 
-/// Decode all Huffman-encoded names
+/// Decode all Huffman-encoded names, decoding the resulting bytes with the given encoding
 pub fn decode_all_names(
     file_name_offset_table: &FileNameOffsetTable,
     file_name_length_table: &FileNameLengthTable,
     serialized_huffman_dict: &SerializedHuffmanDict,
     encoded_huffman_data: &EncodedHuffmanData,
+    name_encoding: NameEncoding,
 ) -> Vec<String> {
+    decode_all_names_bytes(
+        file_name_offset_table,
+        file_name_length_table,
+        serialized_huffman_dict,
+        encoded_huffman_data,
+    )
+    .iter()
+    .map(|decoded_data| name_encoding.decode(decoded_data))
+    .collect()
+}
+
+/// Decode all Huffman-encoded names without decoding the resulting bytes as text
+///
+/// A name is shorter than its length stored in the file name length table if the encoded data
+/// ends before the whole name is decoded
+pub fn decode_all_names_bytes(
+    file_name_offset_table: &FileNameOffsetTable,
+    file_name_length_table: &FileNameLengthTable,
+    serialized_huffman_dict: &SerializedHuffmanDict,
+    encoded_huffman_data: &EncodedHuffmanData,
+) -> Vec<Vec<u8>> {
+    trace_span!("decode_names", count = file_name_offset_table.len());
     let dict = deserialize_huffman_dict(serialized_huffman_dict);
 
     let mut next_offset_iter = file_name_offset_table.iter();
@@ -32,8 +56,7 @@ pub fn decode_all_names(
                     &encoded_huffman_data[(*offset as usize)..(*next_offset as usize)]
                 }
             };
-            let decoded_data = decode_huffman_data(encoded_data, &dict, *length);
-            String::from_utf8_lossy(&decoded_data).to_string()
+            decode_huffman_data(encoded_data, &dict, *length)
         })
         .collect()
 }
@@ -105,6 +128,7 @@ mod tests {
             &archive.file_name_length_table,
             &archive.serialized_huffman_dict,
             &archive.encoded_huffman_data,
+            NameEncoding::default(),
         );
 
         let expected_result_file = File::open("test_data/bfs2004b/fo2a_decoded_names.txt")?;
@@ -0,0 +1,265 @@
+use std::fmt::{Display, Formatter};
+use std::io::SeekFrom;
+
+use binrw::BinRead;
+
+use crate::formats::bfs2004a::ArchiveHeader;
+use crate::formats::bfs2004b::{
+    decode_all_names, decode_all_names_bytes, EncodedHuffmanData, FileNameLengthTable,
+    FileNameOffsetTable, HashTable, MetadataHeader, SerializedHuffmanDict,
+};
+use crate::name_encoding::NameEncoding;
+
+use super::metadata_helpers;
+
+/// Name section of an archive, read without reading any file headers
+///
+/// Contains all folder and file names, indexed by the folder and file IDs stored in
+/// [FileHeader](crate::formats::bfs2004b::FileHeader)s
+#[derive(Debug, Default, Eq, PartialEq, BinRead)]
+#[brw(little)]
+pub struct NameTable {
+    /// The archive header
+    pub archive_header: ArchiveHeader,
+    /// Stores information about the hash size and how many files with specific hash are there
+    pub hash_table: HashTable,
+    /// Header for the metadata section
+    pub metadata_header: MetadataHeader,
+    /// Offsets of specific file names in the Huffman data
+    #[br(
+        seek_before(
+            SeekFrom::Start(
+                metadata_helpers::calculate_metadata_start(&hash_table) as u64 +
+                metadata_header.file_name_offset_table_offset as u64
+            )
+        ),
+        count = metadata_helpers::calculate_metadata_count(
+            metadata_header.file_name_offset_table_offset,
+            &metadata_header,
+            archive_header.header_end,
+            metadata_helpers::calculate_metadata_start(&hash_table)
+        )
+    )]
+    pub file_name_offset_table: FileNameOffsetTable,
+    /// Lengths of specific file names in the Huffman data
+    #[br(
+        seek_before(
+            SeekFrom::Start(
+                metadata_helpers::calculate_metadata_start(&hash_table) as u64 +
+                metadata_header.file_name_length_table_offset as u64
+            )
+        ),
+        count = metadata_helpers::calculate_metadata_count(
+            metadata_header.file_name_length_table_offset,
+            &metadata_header,
+            archive_header.header_end,
+            metadata_helpers::calculate_metadata_start(&hash_table)
+        )
+    )]
+    pub file_name_length_table: FileNameLengthTable,
+    /// Serialized Huffman dictionary
+    #[br(
+        seek_before(
+            SeekFrom::Start(
+                metadata_helpers::calculate_metadata_start(&hash_table) as u64 +
+                metadata_header.huffman_dictionary_offset as u64
+            )
+        ),
+        count = metadata_helpers::calculate_metadata_count(
+            metadata_header.huffman_dictionary_offset,
+            &metadata_header,
+            archive_header.header_end,
+            metadata_helpers::calculate_metadata_start(&hash_table)
+        )
+    )]
+    pub serialized_huffman_dict: SerializedHuffmanDict,
+    /// Encoded Huffman data
+    #[br(
+        seek_before(
+            SeekFrom::Start(
+                metadata_helpers::calculate_metadata_start(&hash_table) as u64 +
+                metadata_header.huffman_data_offset as u64
+            )
+        ),
+        count = metadata_helpers::calculate_metadata_count(
+            metadata_header.huffman_data_offset,
+            &metadata_header,
+            archive_header.header_end,
+            metadata_helpers::calculate_metadata_start(&hash_table)
+        )
+    )]
+    pub encoded_huffman_data: EncodedHuffmanData,
+}
+
+/// Inconsistency between the name table and the file headers referencing it
+#[derive(Debug, Eq, PartialEq)]
+pub enum NameTableProblem {
+    /// A file header references a name ID that is not in the name table
+    MissingName {
+        /// Index of the file header
+        file_index: usize,
+        /// Referenced name ID
+        id: u16,
+    },
+    /// A name is not referenced by any file header
+    UnreferencedName {
+        /// Name ID
+        id: u16,
+    },
+    /// A name decodes to fewer bytes than its length stored in the file name length table
+    LengthMismatch {
+        /// Name ID
+        id: u16,
+        /// Length stored in the file name length table
+        stored: u16,
+        /// Length of the decoded name
+        decoded: u16,
+    },
+}
+
+impl Display for NameTableProblem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NameTableProblem::MissingName { file_index, id } => write!(
+                f,
+                "File header {} references name {}, which does not exist",
+                file_index, id
+            ),
+            NameTableProblem::UnreferencedName { id } => {
+                write!(f, "Name {} is not referenced by any file header", id)
+            }
+            NameTableProblem::LengthMismatch {
+                id,
+                stored,
+                decoded,
+            } => write!(
+                f,
+                "Name {} has a stored length of {}, but only {} bytes could be decoded",
+                id, stored, decoded
+            ),
+        }
+    }
+}
+
+impl NameTable {
+    /// Decode all names with the given encoding, the index of a name is its ID
+    pub fn decode_names(&self, name_encoding: NameEncoding) -> Vec<String> {
+        decode_all_names(
+            &self.file_name_offset_table,
+            &self.file_name_length_table,
+            &self.serialized_huffman_dict,
+            &self.encoded_huffman_data,
+            name_encoding,
+        )
+    }
+
+    /// Check the name table against the folder and file IDs referenced by file headers, given as
+    /// (file header index, folder ID, file ID)
+    pub fn check(
+        &self,
+        referenced_ids: impl IntoIterator<Item = (usize, u16, u16)>,
+    ) -> Vec<NameTableProblem> {
+        let decoded_names = decode_all_names_bytes(
+            &self.file_name_offset_table,
+            &self.file_name_length_table,
+            &self.serialized_huffman_dict,
+            &self.encoded_huffman_data,
+        );
+
+        let mut problems = Vec::new();
+        let mut referenced = vec![false; decoded_names.len()];
+        for (file_index, folder_id, file_id) in referenced_ids {
+            for id in [folder_id, file_id] {
+                match referenced.get_mut(id as usize) {
+                    Some(referenced) => *referenced = true,
+                    None => problems.push(NameTableProblem::MissingName { file_index, id }),
+                }
+            }
+        }
+        problems.extend(
+            referenced
+                .iter()
+                .enumerate()
+                .filter(|(_, referenced)| !**referenced)
+                .map(|(id, _)| NameTableProblem::UnreferencedName { id: id as u16 }),
+        );
+        problems.extend(
+            decoded_names
+                .iter()
+                .zip(&self.file_name_length_table)
+                .enumerate()
+                .filter(|(_, (decoded_name, stored))| decoded_name.len() != **stored as usize)
+                .map(
+                    |(id, (decoded_name, stored))| NameTableProblem::LengthMismatch {
+                        id: id as u16,
+                        stored: *stored,
+                        decoded: decoded_name.len() as u16,
+                    },
+                ),
+        );
+
+        problems
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+    use std::io;
+    use std::io::BufReader;
+
+    use pretty_assertions::assert_eq;
+
+    use crate::formats::bfs2004b::RawArchive;
+
+    use super::*;
+
+    #[test]
+    fn name_table_test() -> io::Result<()> {
+        let test_file = File::open("test_data/bfs2004b/fo2a.bin")?;
+        let mut test_reader = BufReader::new(test_file);
+        let name_table = NameTable::read(&mut test_reader).unwrap();
+
+        let test_file = File::open("test_data/bfs2004b/fo2a.bin")?;
+        let mut test_reader = BufReader::new(test_file);
+        let raw_archive = RawArchive::read(&mut test_reader).unwrap();
+
+        assert_eq!(
+            name_table.decode_names(NameEncoding::default()),
+            decode_all_names(
+                &raw_archive.file_name_offset_table,
+                &raw_archive.file_name_length_table,
+                &raw_archive.serialized_huffman_dict,
+                &raw_archive.encoded_huffman_data,
+                NameEncoding::default(),
+            )
+        );
+        assert_eq!(
+            name_table.decode_names(NameEncoding::default())[0],
+            "01.ogg"
+        );
+        assert_eq!(
+            name_table.check(
+                raw_archive
+                    .file_headers
+                    .iter()
+                    .enumerate()
+                    .map(|(index, file_header)| (
+                        index,
+                        file_header.folder_id,
+                        file_header.file_id
+                    ))
+            ),
+            vec![]
+        );
+        assert_eq!(
+            name_table.check([(0, 0, 0xFFFF)])[0],
+            NameTableProblem::MissingName {
+                file_index: 0,
+                id: 0xFFFF
+            }
+        );
+
+        Ok(())
+    }
+}
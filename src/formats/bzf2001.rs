@@ -33,6 +33,12 @@ impl<R: BufRead + Seek> ArchiveReader<R> for ReadArchive<R> {
         self.raw_archive.archive_header.file_count as u64
     }
 
+    fn header_size(&self) -> u64 {
+        // 0xC - Size of the physical representation of an ArchiveHeader
+        // 0x35 - Size of the physical representation of a FileHeader
+        0xC + self.raw_archive.archive_header.file_count as u64 * 0x35
+    }
+
     fn file_names(&self) -> Vec<String> {
         self.raw_archive
             .file_headers
@@ -72,9 +78,62 @@ impl<R: BufRead + Seek> ArchiveReader<R> for ReadArchive<R> {
             .collect()
     }
 
+    fn unknown_flags(&self) -> Vec<(String, u8)> {
+        self.raw_archive
+            .file_headers
+            .iter()
+            .filter(|file_header| file_header.unknown_flags() != 0)
+            .map(|file_header| (file_header.file_name.clone(), file_header.flags))
+            .collect()
+    }
+
+    fn file_flags(&self) -> Vec<(String, u8)> {
+        self.raw_archive
+            .file_headers
+            .iter()
+            .map(|file_header| (file_header.file_name.clone(), file_header.flags))
+            .collect()
+    }
+
     fn reader(&mut self) -> &mut R {
         &mut self.reader
     }
+
+    fn size_field_offsets(&self) -> Vec<(String, u64, ArchivedFileInfo)> {
+        // 0xC - Size of the physical representation of an ArchiveHeader
+        // 0x35 - Size of the physical representation of a FileHeader
+        // 0x9 - Offset of the packed size in a FileHeader
+        self.raw_archive
+            .file_headers
+            .iter()
+            .enumerate()
+            .map(|(index, file_header)| {
+                (
+                    file_header.file_name.clone(),
+                    0xC + index as u64 * 0x35 + 0x9,
+                    ArchivedFileInfo::from(file_header),
+                )
+            })
+            .collect()
+    }
+
+    fn name_field_offsets(&self) -> Vec<(String, u64, u64)> {
+        // 0xC - Size of the physical representation of an ArchiveHeader
+        // 0x35 - Size of the physical representation of a FileHeader
+        // 0xD - Offset of the file name in a FileHeader
+        self.raw_archive
+            .file_headers
+            .iter()
+            .enumerate()
+            .map(|(index, file_header)| {
+                (
+                    file_header.file_name.clone(),
+                    0xC + index as u64 * 0x35 + 0xD,
+                    0x28,
+                )
+            })
+            .collect()
+    }
 }
 
 /// Checks the magic, version and hash size of the archive to ensure it's a valid Bzf2001 archive
@@ -95,3 +154,24 @@ pub fn check_archive<R: BufRead + Seek>(archive: &mut R) -> Result<(), ReadError
     }
     Ok(())
 }
+
+/// Checks whether the file headers of a Bzf2001 archive appear to be encrypted
+///
+/// Only the file headers are encrypted, the archive header is stored as-is, so the magic and
+/// version checks pass on encrypted archives. The first file header of a decrypted archive only
+/// has known flags set, points past the file headers and has a printable file name.
+pub fn is_likely_encrypted<R: BufRead + Seek>(archive: &mut R) -> Result<bool, ReadError> {
+    archive.seek(SeekFrom::Start(0))?;
+    let archive_header = ArchiveHeader::read(archive)?;
+    if archive_header.file_count == 0 {
+        return Ok(false);
+    }
+    let file_header = FileHeader::read(archive)?;
+    let header_end = 0xC + archive_header.file_count as u64 * 0x35;
+    Ok(file_header.flags & !0x01 != 0
+        || (file_header.data_offset as u64) < header_end
+        || file_header
+            .file_name
+            .chars()
+            .any(|char| !char.is_ascii() || char.is_ascii_control()))
+}
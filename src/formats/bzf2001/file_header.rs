@@ -1,11 +1,13 @@
 use binrw::BinRead;
 
+use crate::name_encoding::NameEncoding;
 use crate::ArchivedFileInfo;
 use crate::CompressionMethod;
 
 /// Header for a single file in a Bzf2001 archive
 #[derive(Debug, Default, Eq, PartialEq, BinRead)]
 #[brw(little)]
+#[br(import(name_encoding: NameEncoding))]
 pub struct FileHeader {
     /// Flags for the archived file
     ///
@@ -19,10 +21,18 @@ pub struct FileHeader {
     /// File size of the file in archive
     pub packed_size: u32,
     /// File name, always 0x28 in size, if less then padded with zeroes
-    #[br(count = 0x28, map = |bytes: Vec<u8>| { String::from_utf8_lossy(&bytes).trim_matches(char::from(0)).to_string() })]
+    #[br(count = 0x28, map = |bytes: Vec<u8>| { name_encoding.decode(&bytes).trim_matches(char::from(0)).to_string() })]
     pub file_name: String,
 }
 
+impl FileHeader {
+    /// Returns the flags set on the file which bfstool does not know the meaning of, all flags
+    /// except `0x01`
+    pub fn unknown_flags(&self) -> u8 {
+        self.flags & !0x01
+    }
+}
+
 impl From<&FileHeader> for ArchivedFileInfo {
     fn from(file_header: &FileHeader) -> Self {
         Self {
@@ -35,6 +45,7 @@ impl From<&FileHeader> for ArchivedFileInfo {
             size: file_header.unpacked_size as u64,
             compressed_size: file_header.packed_size as u64,
             copies: 0,
+            copies_offsets: Vec::new(),
             hash: None,
         }
     }
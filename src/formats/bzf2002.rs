@@ -33,6 +33,10 @@ impl<R: BufRead + Seek> ArchiveReader<R> for ReadArchive<R> {
         self.raw_archive.archive_header.file_count as u64
     }
 
+    fn header_size(&self) -> u64 {
+        self.raw_archive.archive_header.header_size as u64
+    }
+
     fn file_names(&self) -> Vec<String> {
         self.raw_archive
             .file_headers
@@ -72,9 +76,66 @@ impl<R: BufRead + Seek> ArchiveReader<R> for ReadArchive<R> {
             .collect()
     }
 
+    fn unknown_flags(&self) -> Vec<(String, u8)> {
+        self.raw_archive
+            .file_headers
+            .iter()
+            .filter(|file_header| file_header.unknown_flags() != 0)
+            .map(|file_header| (file_header.file_name.clone(), file_header.flags))
+            .collect()
+    }
+
+    fn file_flags(&self) -> Vec<(String, u8)> {
+        self.raw_archive
+            .file_headers
+            .iter()
+            .map(|file_header| (file_header.file_name.clone(), file_header.flags))
+            .collect()
+    }
+
     fn reader(&mut self) -> &mut R {
         &mut self.reader
     }
+
+    fn hash_field_offsets(&self) -> Vec<(String, u64, ArchivedFileInfo)> {
+        // File headers are stored right after the archive header
+        let mut file_header_offset = 0x10;
+        self.raw_archive
+            .file_headers
+            .iter()
+            .filter_map(|file_header| {
+                let hash_field_offset = file_header_offset + 0xD;
+                file_header_offset += 0x13 + file_header.file_name_length as u64;
+                if file_header.flags & 0x04 == 0x04 {
+                    Some((
+                        file_header.file_name.clone(),
+                        hash_field_offset,
+                        ArchivedFileInfo::from(file_header),
+                    ))
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    fn size_field_offsets(&self) -> Vec<(String, u64, ArchivedFileInfo)> {
+        // File headers are stored right after the archive header
+        let mut file_header_offset = 0x10;
+        self.raw_archive
+            .file_headers
+            .iter()
+            .map(|file_header| {
+                let size_field_offset = file_header_offset + 0x9;
+                file_header_offset += 0x13 + file_header.file_name_length as u64;
+                (
+                    file_header.file_name.clone(),
+                    size_field_offset,
+                    ArchivedFileInfo::from(file_header),
+                )
+            })
+            .collect()
+    }
 }
 
 /// Checks the magic, version and hash size of the archive to ensure it's a valid Bzf2002 archive
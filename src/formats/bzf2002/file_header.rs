@@ -1,5 +1,6 @@
 use binrw::BinRead;
 
+use crate::name_encoding::NameEncoding;
 use crate::ArchivedFileInfo;
 use crate::CompressionMethod;
 
@@ -7,6 +8,7 @@ use crate::CompressionMethod;
 
 #[derive(Debug, Default, Eq, PartialEq, BinRead)]
 #[brw(little)]
+#[br(import(name_encoding: NameEncoding))]
 pub struct FileHeader {
     /// Flags for the archived file
     ///
@@ -36,10 +38,18 @@ pub struct FileHeader {
     /// In official archives, file name length can not be 0. If reading an unofficial archive and
     /// the file name length is 0, the file name will be empty and that case needs to be handled
     /// in the user's code
-    #[br(count = file_name_length, map = |bytes: Vec<u8>| { String::from_utf8_lossy(&bytes).to_string() })]
+    #[br(count = file_name_length, map = |bytes: Vec<u8>| { name_encoding.decode(&bytes) })]
     pub file_name: String,
 }
 
+impl FileHeader {
+    /// Returns the flags set on the file which bfstool does not know the meaning of, all flags
+    /// except `0x01` and `0x04`
+    pub fn unknown_flags(&self) -> u8 {
+        self.flags & !0x05
+    }
+}
+
 impl From<&FileHeader> for ArchivedFileInfo {
     fn from(file_header: &FileHeader) -> Self {
         Self {
@@ -52,6 +62,7 @@ impl From<&FileHeader> for ArchivedFileInfo {
             size: file_header.unpacked_size as u64,
             compressed_size: file_header.packed_size as u64,
             copies: 0,
+            copies_offsets: Vec::new(),
             hash: if file_header.flags & 0x04 == 0x04 {
                 Some(file_header.crc32)
             } else {
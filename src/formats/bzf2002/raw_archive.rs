@@ -1,15 +1,18 @@
 use binrw::BinRead;
 
+use crate::name_encoding::NameEncoding;
+
 use super::{ArchiveHeader, FileHeader};
 
 /// Raw archive contents that can be read directly from a .bzf file or written to one
 #[derive(Debug, Default, Eq, PartialEq, BinRead)]
 #[brw(little)]
+#[br(import(name_encoding: NameEncoding))]
 pub struct RawArchive {
     /// The archive header
     pub archive_header: ArchiveHeader,
     /// Offsets for every file header
-    #[br(count = archive_header.file_count)]
+    #[br(args { count: archive_header.file_count as usize, inner: (name_encoding,) })]
     pub file_headers: Vec<FileHeader>,
 }
 
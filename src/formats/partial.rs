@@ -0,0 +1,321 @@
+use std::io;
+use std::io::{Read, Seek, SeekFrom};
+use std::ops::Range;
+
+use super::Format;
+
+/// File magic signature of Bfs2011 archives
+pub const BFS2011_MAGIC: u32 = u32::from_le_bytes(*b"bfs1");
+
+/// File version of Bfs2011 archives
+pub const BFS2011_VERSION: u32 = 0x20111220;
+
+/// File magic signature of Bfs2013 archives
+pub const BFS2013_MAGIC: u32 = u32::from_le_bytes(*b"bbfs");
+
+/// File version of Bfs2013 archives
+pub const BFS2013_VERSION: u32 = 0x20130314;
+
+/// Archive header field read on a best-effort basis
+#[derive(Debug, Eq, PartialEq)]
+pub struct PartialField {
+    /// Field name
+    pub name: &'static str,
+    /// Offset of the field in the archive
+    pub offset: u64,
+    /// Field value
+    pub value: u32,
+    /// Whether the field is known to be at this offset in this format, or only assumed to be
+    /// because it is there in older formats
+    pub confirmed: bool,
+}
+
+/// File located through the assumed file header offset table
+///
+/// All values are read from where they are in the file headers of Bfs2004a archives, none of them
+/// are confirmed
+#[derive(Debug, Eq, PartialEq)]
+pub struct PartialFile {
+    /// Offset of the file header
+    pub header_offset: u64,
+    /// Where the file data is stored, absolute offset
+    pub data_offset: u64,
+    /// File size of the file after unpacking
+    pub unpacked_size: u64,
+    /// File size of the file in archive
+    pub packed_size: u64,
+}
+
+/// Archive of a format which is not fully understood yet, exposing the header fields that parse
+/// cleanly and the regions nothing is known about
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct PartialArchive {
+    /// Header fields which passed sanity checks, in offset order
+    pub fields: Vec<PartialField>,
+    /// End of the archive header, if the assumed header end field is plausible
+    pub header_end: Option<u64>,
+    /// Files in file header offset table order, empty unless every file passed sanity checks
+    pub files: Vec<PartialFile>,
+    /// Regions not assigned to any field, up to the header end, or the end of the archive if the
+    /// header end is unknown
+    pub unknown: Vec<Range<u64>>,
+}
+
+/// Checks the magic and version of the archive to detect formats [read_partial] can read
+pub fn probe_partial<R: Read + Seek>(archive: &mut R) -> Option<Format> {
+    archive.seek(SeekFrom::Start(0)).ok()?;
+    let mut header = [0; 8];
+    archive.read_exact(&mut header).ok()?;
+    let magic = u32::from_le_bytes([header[0], header[1], header[2], header[3]]);
+    let version = u32::from_le_bytes([header[4], header[5], header[6], header[7]]);
+    match (magic, version) {
+        (BFS2011_MAGIC, BFS2011_VERSION) => Some(Format::Bfs2011),
+        (BFS2013_MAGIC, BFS2013_VERSION) => Some(Format::Bfs2013),
+        _ => None,
+    }
+}
+
+/// Size of the start of a Bfs2004a file header containing the flags, data offset, unpacked size
+/// and packed size
+const FILE_HEADER_START_SIZE: u64 = 0x10;
+
+/// Reads whatever can be read from the header of a Bfs2011 or Bfs2013 archive
+///
+/// The magic and version are always reported, but only confirmed if they match the format. The
+/// header end and file count are assumed to follow them like in all other `bfs1` formats, and are
+/// only kept if they fit into the archive. Reading stops at the first field that does not pass,
+/// everything after it is reported as unknown.
+///
+/// If the file count is kept, a file header offset table is assumed to follow it like in Bfs2004a
+/// archives, and the offsets and sizes are read from the start of every file header. Files are
+/// only kept if all file headers lie in the header and all file data lies after it, in the
+/// archive.
+pub fn read_partial<R: Read + Seek>(archive: &mut R, format: Format) -> io::Result<PartialArchive> {
+    let (magic, version) = match format {
+        Format::Bfs2011 => (BFS2011_MAGIC, BFS2011_VERSION),
+        Format::Bfs2013 => (BFS2013_MAGIC, BFS2013_VERSION),
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!(
+                    "{} archives are fully supported, read them normally",
+                    format
+                ),
+            ))
+        }
+    };
+
+    let archive_size = archive.seek(SeekFrom::End(0))?;
+    archive.seek(SeekFrom::Start(0))?;
+    let mut header = Vec::new();
+    archive.by_ref().take(0x10).read_to_end(&mut header)?;
+    let words = header
+        .chunks_exact(4)
+        .map(|word| u32::from_le_bytes([word[0], word[1], word[2], word[3]]))
+        .collect::<Vec<u32>>();
+
+    let mut result = PartialArchive::default();
+    let field = |name, index: usize, confirmed| PartialField {
+        name,
+        offset: index as u64 * 4,
+        value: words[index],
+        confirmed,
+    };
+    if let Some(&found_magic) = words.first() {
+        result.fields.push(field("Magic", 0, found_magic == magic));
+    }
+    if let Some(&found_version) = words.get(1) {
+        result
+            .fields
+            .push(field("Version", 1, found_version == version));
+    }
+    if let Some(&header_end) = words.get(2) {
+        if (0x10..=archive_size).contains(&(header_end as u64)) {
+            result.fields.push(field("Header end", 2, false));
+            result.header_end = Some(header_end as u64);
+        }
+    }
+    if let (Some(header_end), Some(&file_count)) = (result.header_end, words.get(3)) {
+        // Every file needs at least its offset stored in the header
+        if file_count as u64 * 4 <= header_end - 0x10 {
+            result.fields.push(field("File count", 3, false));
+        }
+    }
+
+    if let (Some(header_end), 4) = (result.header_end, result.fields.len()) {
+        result.files = read_files(archive, words[3] as u64, header_end, archive_size)?;
+    }
+
+    let mut known = vec![0..result.fields.len() as u64 * 4];
+    if !result.files.is_empty() {
+        known.push(0x10..0x10 + result.files.len() as u64 * 4);
+        known.extend(
+            result
+                .files
+                .iter()
+                .map(|file| file.header_offset..file.header_offset + FILE_HEADER_START_SIZE),
+        );
+    }
+    known.sort_by_key(|range| range.start);
+    let mut unknown_start = 0;
+    let unknown_end = result.header_end.unwrap_or(archive_size);
+    for range in known {
+        if unknown_start < range.start {
+            result.unknown.push(unknown_start..range.start);
+        }
+        unknown_start = unknown_start.max(range.end);
+    }
+    if unknown_start < unknown_end {
+        result.unknown.push(unknown_start..unknown_end);
+    }
+
+    Ok(result)
+}
+
+/// Reads the assumed file header offset table at 0x10 and the start of every file header
+///
+/// Returns no files if any of them does not pass
+fn read_files<R: Read + Seek>(
+    archive: &mut R,
+    file_count: u64,
+    header_end: u64,
+    archive_size: u64,
+) -> io::Result<Vec<PartialFile>> {
+    let table_end = 0x10 + file_count * 4;
+
+    archive.seek(SeekFrom::Start(0x10))?;
+    let mut table = Vec::new();
+    archive
+        .by_ref()
+        .take(file_count * 4)
+        .read_to_end(&mut table)?;
+
+    let mut files = Vec::new();
+    for offset in table.chunks_exact(4) {
+        let header_offset = u32::from_le_bytes([offset[0], offset[1], offset[2], offset[3]]) as u64;
+        if header_offset < table_end || header_offset + FILE_HEADER_START_SIZE > header_end {
+            return Ok(Vec::new());
+        }
+        archive.seek(SeekFrom::Start(header_offset))?;
+        let mut file_header = [0; FILE_HEADER_START_SIZE as usize];
+        archive.read_exact(&mut file_header)?;
+        let word = |index: usize| {
+            u32::from_le_bytes([
+                file_header[index * 4],
+                file_header[index * 4 + 1],
+                file_header[index * 4 + 2],
+                file_header[index * 4 + 3],
+            ]) as u64
+        };
+        let file = PartialFile {
+            header_offset,
+            data_offset: word(1),
+            unpacked_size: word(2),
+            packed_size: word(3),
+        };
+        if file.data_offset < header_end || file.data_offset + file.packed_size > archive_size {
+            return Ok(Vec::new());
+        }
+        files.push(file);
+    }
+
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn read_partial_test() -> io::Result<()> {
+        // Made up Bfs2011 archive with a 0x40 byte header
+        let mut archive = Vec::new();
+        archive.extend_from_slice(b"bfs1");
+        archive.extend_from_slice(&BFS2011_VERSION.to_le_bytes());
+        archive.extend_from_slice(&0x40u32.to_le_bytes());
+        archive.extend_from_slice(&2u32.to_le_bytes());
+        archive.resize(0x80, 0xAA);
+        let mut archive = Cursor::new(archive);
+
+        assert_eq!(probe_partial(&mut archive), Some(Format::Bfs2011));
+
+        let result = read_partial(&mut archive, Format::Bfs2011)?;
+        assert_eq!(
+            result
+                .fields
+                .iter()
+                .map(|field| (field.name, field.confirmed))
+                .collect::<Vec<_>>(),
+            vec![
+                ("Magic", true),
+                ("Version", true),
+                ("Header end", false),
+                ("File count", false),
+            ]
+        );
+        assert_eq!(result.header_end, Some(0x40));
+        assert_eq!(result.files, vec![]);
+        assert_eq!(result.unknown, vec![0x10..0x40]);
+
+        // Header end pointing past the end of the archive
+        archive.get_mut()[8] = 0xFF;
+        let result = read_partial(&mut archive, Format::Bfs2011)?;
+        assert_eq!(result.fields.len(), 2);
+        assert_eq!(result.header_end, None);
+        assert_eq!(result.unknown, vec![0x8..0x80]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn read_partial_files_test() -> io::Result<()> {
+        // Made up Bfs2011 archive with a 0x40 byte header and 2 files with 0x10 bytes of data
+        let mut archive = Vec::new();
+        archive.extend_from_slice(b"bfs1");
+        archive.extend_from_slice(&BFS2011_VERSION.to_le_bytes());
+        archive.extend_from_slice(&0x40u32.to_le_bytes());
+        archive.extend_from_slice(&2u32.to_le_bytes());
+        archive.extend_from_slice(&0x18u32.to_le_bytes());
+        archive.extend_from_slice(&0x28u32.to_le_bytes());
+        for data_offset in [0x40u32, 0x50] {
+            archive.extend_from_slice(&[0x01, 0x00, 0x00, 0x00]);
+            archive.extend_from_slice(&data_offset.to_le_bytes());
+            archive.extend_from_slice(&0x20u32.to_le_bytes());
+            archive.extend_from_slice(&0x10u32.to_le_bytes());
+        }
+        archive.resize(0x60, 0xAA);
+        let mut archive = Cursor::new(archive);
+
+        let result = read_partial(&mut archive, Format::Bfs2011)?;
+        assert_eq!(
+            result.files,
+            vec![
+                PartialFile {
+                    header_offset: 0x18,
+                    data_offset: 0x40,
+                    unpacked_size: 0x20,
+                    packed_size: 0x10,
+                },
+                PartialFile {
+                    header_offset: 0x28,
+                    data_offset: 0x50,
+                    unpacked_size: 0x20,
+                    packed_size: 0x10,
+                },
+            ]
+        );
+        assert_eq!(result.unknown, vec![0x38..0x40]);
+
+        // Data of the second file past the end of the archive
+        archive.get_mut()[0x34] = 0xFF;
+        let result = read_partial(&mut archive, Format::Bfs2011)?;
+        assert_eq!(result.files, vec![]);
+        assert_eq!(result.unknown, vec![0x10..0x40]);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,93 @@
+use std::ops::Range;
+
+/// Report about how file data is laid out in an archive
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct LayoutReport {
+    /// Regions not referenced by any file
+    pub gaps: Vec<Range<u64>>,
+    /// Regions referenced by more than one file, except for files sharing the exact same region
+    pub overlaps: Vec<Range<u64>>,
+    /// Regions referenced by multiple files with the exact same offset and size, together with
+    /// the amount of files referencing them
+    pub shared: Vec<(Range<u64>, u64)>,
+}
+
+/// Analyzes the file data regions of an archive with data stored between `data_start` and
+/// `data_end`, reporting unreferenced, overlapping and shared regions
+pub fn analyze_layout(
+    data_start: u64,
+    data_end: u64,
+    regions: impl IntoIterator<Item = Range<u64>>,
+) -> LayoutReport {
+    let mut regions = regions
+        .into_iter()
+        .filter(|region| !region.is_empty())
+        .collect::<Vec<Range<u64>>>();
+    regions.sort_by_key(|region| (region.start, region.end));
+
+    let mut report = LayoutReport::default();
+    let mut covered_until = data_start;
+    let mut previous_region = None;
+    for region in regions {
+        if previous_region.as_ref() == Some(&region) {
+            match report.shared.last_mut() {
+                Some((shared_region, count)) if *shared_region == region => *count += 1,
+                _ => report.shared.push((region, 2)),
+            }
+            continue;
+        }
+        if region.start > covered_until {
+            report.gaps.push(covered_until..region.start);
+        } else if region.start < covered_until {
+            report
+                .overlaps
+                .push(region.start..region.end.min(covered_until));
+        }
+        covered_until = covered_until.max(region.end);
+        previous_region = Some(region);
+    }
+    if covered_until < data_end {
+        report.gaps.push(covered_until..data_end);
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn analyze_test() {
+        let result = analyze_layout(
+            0x10,
+            0x100,
+            vec![
+                0x10..0x20,
+                0x24..0x30,
+                0x24..0x30,
+                0x24..0x30,
+                0x2C..0x40,
+                0x50..0x50,
+            ],
+        );
+
+        assert_eq!(
+            result,
+            LayoutReport {
+                gaps: vec![0x20..0x24, 0x40..0x100],
+                overlaps: vec![0x2C..0x30],
+                shared: vec![(0x24..0x30, 3)],
+            }
+        );
+    }
+
+    #[test]
+    fn analyze_test_contiguous() {
+        let result = analyze_layout(0x10, 0x30, vec![0x20..0x30, 0x10..0x20]);
+
+        assert_eq!(result, LayoutReport::default());
+    }
+}
@@ -58,24 +58,58 @@
 //! - [Sewer56's FlatOut 2 Mod Loader](https://github.com/Sewer56/FlatOut2.Utils.ModLoader) adds
 //! support for files compressed with Zstandard (zstd). The files get handled automatically and no
 //! code tweaks are required.
+//!
+//! # Profiling
+//!
+//! With the `tracing` feature enabled, reading archives, decoding file names, compressing and
+//! decompressing are instrumented with [tracing](https://docs.rs/tracing) spans at the trace
+//! level. Any tracing subscriber can record them, for example `tracing-flame` to create
+//! flamegraphs of slow archives.
+
+/// Enters a trace level span until the end of the current scope if the `tracing` feature is
+/// enabled, does nothing otherwise
+macro_rules! trace_span {
+    ($($arguments:tt)*) => {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::trace_span!($($arguments)*).entered();
+    };
+}
 
-pub use archive_reader::{read_archive, read_archive_file};
+pub use archive_reader::{
+    probe, read_archive, read_archive_file, read_archive_file_with_options,
+    read_archive_with_options, ReadOptions,
+};
 pub use archived_file_info::ArchivedFileInfo;
 pub use compression::CompressionMethod;
 pub use formats::Format;
+pub use warning::Warning;
 
 /// Provides generics to read a format
 pub mod archive_reader;
 /// Provides information structs about an archived file
 pub mod archived_file_info;
+/// Provides the registry for custom compression codecs
+pub mod codec;
 /// Provides compression utilities
-mod compression;
+pub mod compression;
+/// Provides the registry for post-extract file converters
+pub mod converter;
 /// Provides all encryption utilities
 pub mod crypt;
 /// Provides display utilities
 mod display;
+/// Provides the registry for archive formats implemented outside of bfstool
+pub mod format_provider;
 /// Provides all the formats available in the tool as well as their implementations
 pub mod formats;
 /// Provides structs for reading/writing a Keys.toml file
 #[cfg(feature = "keys")]
 pub mod keys;
+/// Provides archive layout analysis
+pub mod layout;
+/// Provides the configurable encoding of file names
+pub mod name_encoding;
+/// Provides a clonable archive handle for extracting files from multiple threads
+pub mod shared_archive;
+/// Provides warnings about archives that can still be read
+pub mod warning;
@@ -0,0 +1,68 @@
+pub use encoding_rs::Encoding;
+
+/// Encoding file names are stored in, given to [read_archive_with_options] in [ReadOptions]
+///
+/// Official archives only contain ASCII file names, so another encoding is only needed for
+/// unofficial archives created with tools using a legacy codepage, for example `windows-1251`.
+/// Defaults to UTF-8.
+///
+/// [read_archive_with_options]: crate::read_archive_with_options
+/// [ReadOptions]: crate::ReadOptions
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct NameEncoding(pub &'static Encoding);
+
+impl Default for NameEncoding {
+    fn default() -> Self {
+        Self(encoding_rs::UTF_8)
+    }
+}
+
+impl NameEncoding {
+    /// Returns the name of the encoding
+    pub fn name(&self) -> &'static str {
+        self.0.name()
+    }
+
+    /// Decodes a file name stored in an archive, replacing invalid sequences with U+FFFD
+    pub fn decode(&self, bytes: &[u8]) -> String {
+        self.0.decode_without_bom_handling(bytes).0.into_owned()
+    }
+
+    /// Encodes a file name to be stored in an archive
+    ///
+    /// Returns `None` if the name contains characters which can not be represented in the
+    /// encoding
+    pub fn encode(&self, name: &str) -> Option<Vec<u8>> {
+        let (bytes, _, unmappable) = self.0.encode(name);
+        if unmappable {
+            None
+        } else {
+            Some(bytes.into_owned())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn name_encoding_test() {
+        let utf_8 = NameEncoding::default();
+        assert_eq!(utf_8.decode(b"data/credits.txt"), "data/credits.txt");
+        assert_eq!(
+            utf_8.decode(b"tra\x9Dov\xE1.bgm"),
+            "tra\u{FFFD}ov\u{FFFD}.bgm"
+        );
+
+        let windows_1250 = NameEncoding(encoding_rs::WINDOWS_1250);
+        assert_eq!(windows_1250.decode(b"tra\x9Dov\xE1.bgm"), "traťová.bgm");
+        assert_eq!(
+            windows_1250.encode("traťová.bgm"),
+            Some(b"tra\x9Dov\xE1.bgm".to_vec())
+        );
+        assert_eq!(windows_1250.encode("трасса.bgm"), None);
+    }
+}
@@ -0,0 +1,121 @@
+use std::fs::File;
+use std::io;
+#[cfg(any(unix, windows))]
+use std::io::Read;
+use std::io::{BufReader, Write};
+#[cfg(not(any(unix, windows)))]
+use std::io::{Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::archive_reader::{open_archive_file, read_archive_file, ReadError};
+use crate::compression::decompress;
+use crate::{ArchivedFileInfo, Format};
+
+/// Clonable handle to an archive file, allowing files to be extracted from multiple threads at
+/// once, for example previewing one file while others are being extracted
+///
+/// File headers are only read when opening the archive. On Unix and Windows all clones share one
+/// file handle and extract using positional reads, which do not depend on the file position, so
+/// extractions never wait for each other. On other platforms every extraction opens the archive
+/// file again.
+#[derive(Clone, Debug)]
+pub struct SharedArchive {
+    path: Arc<Path>,
+    #[cfg(any(unix, windows))]
+    file: Arc<File>,
+    files: Arc<[(String, ArchivedFileInfo)]>,
+}
+
+impl SharedArchive {
+    /// Open an archive file with the provided format
+    ///
+    /// If `force` is true then Magic / Version / Hash size check are skipped
+    pub fn open(archive: &PathBuf, archive_format: Format, force: bool) -> Result<Self, ReadError> {
+        let archive_reader = read_archive_file(archive, archive_format, force)?;
+        Ok(Self {
+            path: Arc::from(archive.as_path()),
+            #[cfg(any(unix, windows))]
+            file: Arc::new(open_archive_file(archive)?),
+            files: archive_reader
+                .multiple_file_info(archive_reader.file_names())
+                .into(),
+        })
+    }
+
+    /// Returns the path of the archive file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns file count of the archive
+    pub fn file_count(&self) -> u64 {
+        self.files.len() as u64
+    }
+
+    /// Returns file names of all files in the archive
+    pub fn file_names(&self) -> Vec<String> {
+        self.files
+            .iter()
+            .map(|(file_name, _)| file_name.clone())
+            .collect()
+    }
+
+    /// Returns ArchivedFileInfo for the given file name, if any
+    ///
+    /// If there are multiple files with the same name, all of them are returned
+    pub fn file_info(&self, file_name: &str) -> Vec<ArchivedFileInfo> {
+        self.files
+            .iter()
+            .filter(|(name, _)| name == file_name)
+            .map(|(_, archived_file_info)| archived_file_info.clone())
+            .collect()
+    }
+
+    /// Extracts the data of a single file described by `archived_file_info` into `writer`
+    ///
+    /// Returns the amount of bytes written
+    pub fn extract_file_data(
+        &self,
+        archived_file_info: &ArchivedFileInfo,
+        writer: &mut dyn Write,
+    ) -> io::Result<u64> {
+        #[cfg(any(unix, windows))]
+        let mut reader = BufReader::new(PositionalReader {
+            file: &self.file,
+            position: archived_file_info.offset,
+        });
+        #[cfg(not(any(unix, windows)))]
+        let mut reader = {
+            let mut reader = BufReader::new(open_archive_file(&self.path)?);
+            reader.seek(SeekFrom::Start(archived_file_info.offset))?;
+            reader
+        };
+        decompress(
+            &mut reader,
+            writer,
+            archived_file_info.compressed_size,
+            archived_file_info.compression_method,
+        )
+    }
+}
+
+/// Reader reading a file from its own position using positional reads, leaving the position of
+/// the file handle itself unused
+#[cfg(any(unix, windows))]
+struct PositionalReader<'a> {
+    file: &'a File,
+    position: u64,
+}
+
+#[cfg(any(unix, windows))]
+impl Read for PositionalReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        #[cfg(unix)]
+        let read = std::os::unix::fs::FileExt::read_at(self.file, buf, self.position)?;
+        #[cfg(windows)]
+        let read = std::os::windows::fs::FileExt::seek_read(self.file, buf, self.position)?;
+        self.position += read as u64;
+        Ok(read)
+    }
+}
@@ -0,0 +1,78 @@
+use std::fmt::{Display, Formatter};
+
+use crate::Format;
+
+/// Problems found in an archive that do not prevent reading it
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Warning {
+    /// The archive was read with the format checks skipped, but it does not pass them
+    FormatChecksFailed {
+        /// Format the archive was read as
+        format: Format,
+        /// Format the archive passes the checks of, if any
+        detected: Option<Format>,
+    },
+    /// Some files have no name, they are extracted with a name matching their offset
+    UnnamedFiles {
+        /// Amount of files without a name
+        count: u64,
+    },
+    /// Some file names only differ in case, extracting them on a case-insensitive file system
+    /// (Windows, macOS) overwrites all but one of them
+    CaseCollisions {
+        /// Groups of file names only differing in case
+        names: Vec<Vec<String>>,
+    },
+    /// Some files have flags set which bfstool does not know the meaning of, they are extracted
+    /// as if the flags were not set
+    UnknownFlags {
+        /// Names and flags of the files
+        files: Vec<(String, u8)>,
+    },
+}
+
+impl Display for Warning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Warning::FormatChecksFailed {
+                format,
+                detected: Some(detected),
+            } => write!(
+                f,
+                "Archive does not pass the {} format checks, but passes the {} ones",
+                format, detected
+            ),
+            Warning::FormatChecksFailed {
+                format,
+                detected: None,
+            } => write!(f, "Archive does not pass the {} format checks", format),
+            Warning::UnnamedFiles { count } => write!(
+                f,
+                "{} file(s) have no name and will be extracted with a name matching their offset",
+                count
+            ),
+            Warning::CaseCollisions { names } => write!(
+                f,
+                "{} group(s) of file names only differ in case and overwrite each other when \
+                 extracted on a case-insensitive file system: {}",
+                names.len(),
+                names
+                    .iter()
+                    .map(|names| names.join(", "))
+                    .collect::<Vec<String>>()
+                    .join("; ")
+            ),
+            Warning::UnknownFlags { files } => write!(
+                f,
+                "{} file(s) have unknown flags set, which are ignored: {}",
+                files.len(),
+                files
+                    .iter()
+                    .map(|(file_name, flags)| format!("{} ({:02X})", file_name, flags))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+        }
+    }
+}
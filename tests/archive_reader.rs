@@ -1,8 +1,11 @@
 use std::error::Error;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Cursor};
 use std::path::PathBuf;
 
 use pretty_assertions::assert_eq;
 
+use bfstool::archive_reader::ReadError;
 use bfstool::ArchivedFileInfo;
 use bfstool::CompressionMethod;
 
@@ -24,6 +27,7 @@ fn test_bfs2004a() -> Result<(), Box<dyn Error>> {
             size: 0x44F,
             compressed_size: 0x1D7,
             copies: 0,
+            copies_offsets: vec![],
             hash: Some(0xF6260C6E),
         }]
     );
@@ -42,6 +46,7 @@ fn test_bfs2004a() -> Result<(), Box<dyn Error>> {
                 size: 0x44F,
                 compressed_size: 0x1D7,
                 copies: 0,
+                copies_offsets: vec![],
                 hash: Some(0xF6260C6E),
             }
         )]
@@ -76,6 +81,7 @@ fn test_bfs2004b() -> Result<(), Box<dyn Error>> {
             size: 0x40000,
             compressed_size: 0x12664,
             copies: 0,
+            copies_offsets: vec![],
             hash: Some(0x487CE316),
         }]
     );
@@ -94,6 +100,7 @@ fn test_bfs2004b() -> Result<(), Box<dyn Error>> {
                     size: 0x40000,
                     compressed_size: 0x12664,
                     copies: 0,
+                    copies_offsets: vec![],
                     hash: Some(0x487CE316),
                 }
             ),
@@ -105,6 +112,7 @@ fn test_bfs2004b() -> Result<(), Box<dyn Error>> {
                     size: 0x9187,
                     compressed_size: 0x2AB8,
                     copies: 0,
+                    copies_offsets: vec![],
                     hash: Some(0xAC3BC1F0),
                 }
             ),
@@ -137,6 +145,7 @@ fn test_bfs2007() -> Result<(), Box<dyn Error>> {
             size: 0xAB38,
             compressed_size: 0x8749,
             copies: 0,
+            copies_offsets: vec![],
             hash: Some(0x22434A64),
         }]
     );
@@ -155,6 +164,7 @@ fn test_bfs2007() -> Result<(), Box<dyn Error>> {
                     size: 0xAB38,
                     compressed_size: 0x8749,
                     copies: 0,
+                    copies_offsets: vec![],
                     hash: Some(0x22434A64),
                 }
             ),
@@ -166,6 +176,7 @@ fn test_bfs2007() -> Result<(), Box<dyn Error>> {
                     size: 0x155F0,
                     compressed_size: 0x155F0,
                     copies: 0,
+                    copies_offsets: vec![],
                     hash: Some(0xFBE9D4BB),
                 }
             ),
@@ -198,6 +209,7 @@ fn test_bzf2001() -> Result<(), Box<dyn Error>> {
             size: 0xF5F,
             compressed_size: 0x78D,
             copies: 0,
+            copies_offsets: vec![],
             hash: None,
         }]
     );
@@ -216,6 +228,7 @@ fn test_bzf2001() -> Result<(), Box<dyn Error>> {
                     size: 0xF5F,
                     compressed_size: 0x78D,
                     copies: 0,
+                    copies_offsets: vec![],
                     hash: None,
                 }
             ),
@@ -227,6 +240,7 @@ fn test_bzf2001() -> Result<(), Box<dyn Error>> {
                     size: 0x1D1B,
                     compressed_size: 0xD26,
                     copies: 0,
+                    copies_offsets: vec![],
                     hash: None,
                 }
             ),
@@ -259,6 +273,7 @@ fn test_bzf2002() -> Result<(), Box<dyn Error>> {
             size: 0x123C,
             compressed_size: 0x3B8,
             copies: 0,
+            copies_offsets: vec![],
             hash: None,
         }]
     );
@@ -277,6 +292,7 @@ fn test_bzf2002() -> Result<(), Box<dyn Error>> {
                     size: 0x123C,
                     compressed_size: 0x3B8,
                     copies: 0,
+                    copies_offsets: vec![],
                     hash: None,
                 }
             ),
@@ -288,6 +304,7 @@ fn test_bzf2002() -> Result<(), Box<dyn Error>> {
                     size: 0x3DD,
                     compressed_size: 0x10C,
                     copies: 0,
+                    copies_offsets: vec![],
                     hash: None,
                 }
             ),
@@ -296,3 +313,516 @@ fn test_bzf2002() -> Result<(), Box<dyn Error>> {
 
     Ok(())
 }
+
+#[test]
+fn test_probe() -> Result<(), Box<dyn Error>> {
+    let test_files = [
+        ("test_data/bfs2004a/europe.bin", bfstool::Format::Bfs2004a),
+        ("test_data/bfs2004a/common1.bin", bfstool::Format::Bfs2004a),
+        ("test_data/bfs2004b/fo2a.bin", bfstool::Format::Bfs2004b),
+        ("test_data/bfs2007/fouc_data.bin", bfstool::Format::Bfs2007),
+        (
+            "test_data/bfs2007/fouc_x360_data.bin",
+            bfstool::Format::Bfs2007,
+        ),
+        ("test_data/bzf2001/language.bin", bfstool::Format::Bzf2001),
+        (
+            "test_data/bzf2002/demo_Shader.bin",
+            bfstool::Format::Bzf2002,
+        ),
+    ];
+
+    for (file_name, format) in test_files {
+        let mut reader = BufReader::new(File::open(file_name)?);
+        assert_eq!(bfstool::probe(&mut reader), Some(format));
+        assert!(!bfstool::archive_reader::probe_encrypted(
+            &mut reader,
+            format
+        ));
+    }
+
+    let mut reader = Cursor::new(b"not an archive".to_vec());
+    assert_eq!(bfstool::probe(&mut reader), None);
+
+    Ok(())
+}
+
+#[test]
+fn test_bzf2001_encrypted() -> Result<(), Box<dyn Error>> {
+    let input = BufReader::new(File::open("test_data/bzf2001/language.bin")?);
+    let mut encrypted_data = BufWriter::new(Cursor::new(Vec::new()));
+    // Made up key, only used to make the file headers unreadable
+    let key = std::array::from_fn(|index| (index as u8).wrapping_mul(7).wrapping_add(3));
+    bfstool::crypt::bzf2001::encrypt(input, &mut encrypted_data, key)?;
+    let mut encrypted_data = encrypted_data.into_inner()?;
+
+    assert_eq!(
+        bfstool::probe(&mut encrypted_data),
+        Some(bfstool::Format::Bzf2001)
+    );
+    assert!(bfstool::archive_reader::probe_encrypted(
+        &mut encrypted_data,
+        bfstool::Format::Bzf2001
+    ));
+
+    let result = bfstool::read_archive(encrypted_data, bfstool::Format::Bzf2001, false);
+
+    assert!(matches!(
+        result,
+        Err(ReadError::LikelyEncrypted {
+            format: bfstool::Format::Bzf2001
+        })
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_unsupported_format() {
+    let result = bfstool::read_archive(
+        Cursor::new(b"bfs1".to_vec()),
+        bfstool::Format::Bfs2011,
+        false,
+    );
+
+    assert!(matches!(
+        result,
+        Err(ReadError::UnsupportedFormat {
+            format: bfstool::Format::Bfs2011
+        })
+    ));
+}
+
+#[test]
+fn test_hash_field_offsets() -> Result<(), Box<dyn Error>> {
+    let archive = bfstool::read_archive_file(
+        &PathBuf::from("test_data/bfs2004a/europe.bin"),
+        bfstool::Format::Bfs2004a,
+        false,
+    )?;
+    assert_eq!(
+        archive.hash_field_offsets(),
+        vec![(
+            "data/language/version.ini".to_string(),
+            0xFBC,
+            ArchivedFileInfo {
+                offset: 0xFDC,
+                compression_method: CompressionMethod::Zlib,
+                size: 0x44F,
+                compressed_size: 0x1D7,
+                copies: 0,
+                copies_offsets: vec![],
+                hash: Some(0xF6260C6E),
+            }
+        )]
+    );
+
+    let archive = bfstool::read_archive_file(
+        &PathBuf::from("test_data/bfs2004b/fo2a.bin"),
+        bfstool::Format::Bfs2004b,
+        false,
+    )?;
+    let hash_field_offsets = archive.hash_field_offsets();
+    assert_eq!(hash_field_offsets.len(), 6349);
+    assert_eq!(hash_field_offsets[0].1, 0x11F60);
+
+    let archive = bfstool::read_archive_file(
+        &PathBuf::from("test_data/bfs2007/fouc_data.bin"),
+        bfstool::Format::Bfs2007,
+        false,
+    )?;
+    assert_eq!(archive.hash_field_offsets()[0].1, 0x16598);
+
+    Ok(())
+}
+
+#[test]
+fn test_format_mismatch() {
+    let result = bfstool::read_archive_file(
+        &PathBuf::from("test_data/bfs2004b/fo2a.bin"),
+        bfstool::Format::Bfs2004a,
+        false,
+    );
+    assert!(matches!(
+        result,
+        Err(ReadError::FormatMismatch {
+            expected: bfstool::Format::Bfs2004a,
+            detected: bfstool::Format::Bfs2004b,
+        })
+    ));
+
+    let result = bfstool::read_archive_file(
+        &PathBuf::from("test_data/bfs2004a/europe.bin"),
+        bfstool::Format::Bfs2004b,
+        false,
+    );
+    assert!(matches!(
+        result,
+        Err(ReadError::FormatMismatch {
+            expected: bfstool::Format::Bfs2004b,
+            detected: bfstool::Format::Bfs2004a,
+        })
+    ));
+}
+
+#[test]
+fn test_find_nested_archives() -> Result<(), Box<dyn Error>> {
+    let nested_archive = std::fs::read("test_data/bfs2004a/europe.bin")?;
+    let file_name = b"europe.bfs";
+    let data_offset = 0x10 + 0x13 + file_name.len() as u32;
+
+    // Made up Bzf2002 archive storing europe.bin uncompressed
+    let mut archive = Vec::new();
+    archive.extend_from_slice(b"bzf2");
+    archive.extend_from_slice(&0x20021011u32.to_le_bytes());
+    archive.extend_from_slice(&data_offset.to_le_bytes());
+    archive.extend_from_slice(&1u32.to_le_bytes());
+    archive.push(0x00);
+    archive.extend_from_slice(&data_offset.to_le_bytes());
+    archive.extend_from_slice(&(nested_archive.len() as u32).to_le_bytes());
+    archive.extend_from_slice(&(nested_archive.len() as u32).to_le_bytes());
+    archive.extend_from_slice(&0u32.to_le_bytes());
+    archive.extend_from_slice(&(file_name.len() as u16).to_le_bytes());
+    archive.extend_from_slice(file_name);
+    archive.extend_from_slice(&nested_archive);
+
+    let mut archive = bfstool::read_archive(Cursor::new(archive), bfstool::Format::Bzf2002, false)?;
+    let nested_archives = bfstool::archive_reader::find_nested_archives(archive.as_mut())?;
+
+    assert_eq!(nested_archives.len(), 1);
+    assert_eq!(nested_archives[0].0, "europe.bfs");
+    assert_eq!(nested_archives[0].2, bfstool::Format::Bfs2004a);
+
+    Ok(())
+}
+
+#[test]
+fn test_file_flags() -> Result<(), Box<dyn Error>> {
+    let archive = bfstool::read_archive_file(
+        &PathBuf::from("test_data/bfs2004a/europe.bin"),
+        bfstool::Format::Bfs2004a,
+        false,
+    )?;
+    assert_eq!(
+        archive.file_flags(),
+        vec![("data/language/version.ini".to_string(), 0x05)]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_warnings() -> Result<(), Box<dyn Error>> {
+    let mut reader = BufReader::new(File::open("test_data/bfs2004b/fo2a.bin")?);
+    assert_eq!(
+        bfstool::archive_reader::check_forced_format(&mut reader, bfstool::Format::Bfs2004a),
+        Some(bfstool::Warning::FormatChecksFailed {
+            format: bfstool::Format::Bfs2004a,
+            detected: Some(bfstool::Format::Bfs2004b),
+        })
+    );
+    assert_eq!(
+        bfstool::archive_reader::check_forced_format(&mut reader, bfstool::Format::Bfs2004b),
+        None
+    );
+
+    let archive = bfstool::read_archive(reader, bfstool::Format::Bfs2004b, false)?;
+    assert_eq!(archive.warnings(), vec![]);
+
+    let file_names = ["Data/a.DDS", "data/a.dds", "data/b.dds", "data/a.dds"].map(String::from);
+    assert_eq!(
+        bfstool::archive_reader::case_collisions(&file_names),
+        vec![vec!["Data/a.DDS".to_string(), "data/a.dds".to_string()]]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_shared_archive() -> Result<(), Box<dyn Error>> {
+    let stored_file = std::fs::read("test_data/bfs2004a/europe.bin")?;
+    let file_name = b"europe.bfs";
+    let data_offset = 0x10 + 0x13 + file_name.len() as u32;
+
+    // Made up Bzf2002 archive storing europe.bin uncompressed
+    let mut archive = Vec::new();
+    archive.extend_from_slice(b"bzf2");
+    archive.extend_from_slice(&0x20021011u32.to_le_bytes());
+    archive.extend_from_slice(&data_offset.to_le_bytes());
+    archive.extend_from_slice(&1u32.to_le_bytes());
+    archive.push(0x00);
+    archive.extend_from_slice(&data_offset.to_le_bytes());
+    archive.extend_from_slice(&(stored_file.len() as u32).to_le_bytes());
+    archive.extend_from_slice(&(stored_file.len() as u32).to_le_bytes());
+    archive.extend_from_slice(&0u32.to_le_bytes());
+    archive.extend_from_slice(&(file_name.len() as u16).to_le_bytes());
+    archive.extend_from_slice(file_name);
+    archive.extend_from_slice(&stored_file);
+    let archive_path = std::env::temp_dir().join("bfstool_shared_archive_test.bin");
+    std::fs::write(&archive_path, &archive)?;
+
+    let archive = bfstool::shared_archive::SharedArchive::open(
+        &archive_path,
+        bfstool::Format::Bzf2002,
+        false,
+    )?;
+    assert_eq!(archive.file_names(), vec!["europe.bfs".to_string()]);
+
+    let threads = (0..4)
+        .map(|_| {
+            let archive = archive.clone();
+            std::thread::spawn(move || {
+                let mut data = Vec::new();
+                archive
+                    .extract_file_data(&archive.file_info("europe.bfs")[0], &mut data)
+                    .map(|_| data)
+            })
+        })
+        .collect::<Vec<_>>();
+    for thread in threads {
+        assert_eq!(thread.join().unwrap()?, stored_file);
+    }
+
+    std::fs::remove_file(&archive_path)?;
+
+    Ok(())
+}
+
+#[test]
+fn test_ignore_packed_size() -> Result<(), Box<dyn Error>> {
+    let data = b"data/language/version.ini ".repeat(64);
+    let mut compressed = Vec::new();
+    bfstool::compression::compress(
+        &mut data.as_slice(),
+        &mut compressed,
+        CompressionMethod::Zlib,
+        None,
+    )?;
+    let file_name = b"version.ini";
+    let data_offset = 0x10 + 0x13 + file_name.len() as u32;
+
+    // Made up Bzf2002 archive storing a zlib compressed file with a damaged packed size
+    let mut archive = Vec::new();
+    archive.extend_from_slice(b"bzf2");
+    archive.extend_from_slice(&0x20021011u32.to_le_bytes());
+    archive.extend_from_slice(&data_offset.to_le_bytes());
+    archive.extend_from_slice(&1u32.to_le_bytes());
+    archive.push(0x01);
+    archive.extend_from_slice(&data_offset.to_le_bytes());
+    archive.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    archive.extend_from_slice(&0x10u32.to_le_bytes());
+    archive.extend_from_slice(&0u32.to_le_bytes());
+    archive.extend_from_slice(&(file_name.len() as u16).to_le_bytes());
+    archive.extend_from_slice(file_name);
+    archive.extend_from_slice(&compressed);
+
+    let mut archive = bfstool::read_archive(Cursor::new(archive), bfstool::Format::Bzf2002, false)?;
+    let output = std::env::temp_dir().join("bfstool_ignore_packed_size_test");
+    let file_names = archive.file_names();
+    archive.extract_files_with_options(
+        file_names,
+        &output,
+        bfstool::archive_reader::ExtractOptions {
+            ignore_packed_size: true,
+            ..Default::default()
+        },
+        Box::new(|_, _| {}),
+    )?;
+
+    let extracted = std::fs::read(output.join("version.ini"))?;
+    std::fs::remove_dir_all(&output)?;
+    assert_eq!(extracted, data);
+
+    Ok(())
+}
+
+#[test]
+fn test_extract_duplicates() -> Result<(), Box<dyn Error>> {
+    // Two uncompressed files sharing the same data stored twice, and a third file with the same
+    // name as the first one overwriting it
+    let files: [(&[u8], &[u8]); 4] = [
+        (b"a.txt", b"shared"),
+        (b"b.txt", b"shared"),
+        (b"a.txt", b"other"),
+        (b"c.txt", b"shared"),
+    ];
+    let header_size = 0x10
+        + files
+            .iter()
+            .map(|(file_name, _)| 0x13 + file_name.len() as u32)
+            .sum::<u32>();
+    let shared_offset = header_size;
+    let other_offset = header_size + b"shared".len() as u32;
+
+    // Made up Bzf2002 archive
+    let mut archive = Vec::new();
+    archive.extend_from_slice(b"bzf2");
+    archive.extend_from_slice(&0x20021011u32.to_le_bytes());
+    archive.extend_from_slice(&header_size.to_le_bytes());
+    archive.extend_from_slice(&(files.len() as u32).to_le_bytes());
+    for (file_name, data) in files {
+        let data_offset = if data == b"shared" {
+            shared_offset
+        } else {
+            other_offset
+        };
+        archive.push(0x00);
+        archive.extend_from_slice(&data_offset.to_le_bytes());
+        archive.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        archive.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        archive.extend_from_slice(&0u32.to_le_bytes());
+        archive.extend_from_slice(&(file_name.len() as u16).to_le_bytes());
+        archive.extend_from_slice(file_name);
+    }
+    archive.extend_from_slice(b"shared");
+    archive.extend_from_slice(b"other");
+
+    let mut archive = bfstool::read_archive(Cursor::new(archive), bfstool::Format::Bzf2002, false)?;
+    let file_names = archive.file_names();
+    for link_duplicates in [false, true] {
+        let output = std::env::temp_dir().join(format!(
+            "bfstool_extract_duplicates_test_{}",
+            link_duplicates
+        ));
+        archive.extract_files_with_options(
+            file_names.clone(),
+            &output,
+            bfstool::archive_reader::ExtractOptions {
+                link_duplicates,
+                ..Default::default()
+            },
+            Box::new(|_, _| {}),
+        )?;
+
+        let extracted =
+            ["a.txt", "b.txt", "c.txt"].map(|file_name| std::fs::read(output.join(file_name)));
+        std::fs::remove_dir_all(&output)?;
+        let [a, b, c] = extracted;
+        assert_eq!(a?, b"other");
+        assert_eq!(b?, b"shared");
+        assert_eq!(c?, b"shared");
+    }
+
+    Ok(())
+}
+
+/// Converter appending a line to the extracted file
+struct AppendConverter;
+
+impl bfstool::converter::Converter for AppendConverter {
+    fn name(&self) -> &str {
+        "append"
+    }
+
+    fn convert(&self, path: &std::path::Path) -> std::io::Result<()> {
+        let mut data = std::fs::read(path)?;
+        data.extend_from_slice(b"converted");
+        std::fs::write(path, data)
+    }
+}
+
+#[test]
+fn test_convert() -> Result<(), Box<dyn Error>> {
+    let data = b"TIM2";
+    let file_name = b"menu.tm2";
+    let data_offset = 0x10 + 0x13 + file_name.len() as u32;
+
+    // Made up Bzf2002 archive storing a single uncompressed file
+    let mut archive = Vec::new();
+    archive.extend_from_slice(b"bzf2");
+    archive.extend_from_slice(&0x20021011u32.to_le_bytes());
+    archive.extend_from_slice(&data_offset.to_le_bytes());
+    archive.extend_from_slice(&1u32.to_le_bytes());
+    archive.push(0x00);
+    archive.extend_from_slice(&data_offset.to_le_bytes());
+    archive.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    archive.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    archive.extend_from_slice(&0u32.to_le_bytes());
+    archive.extend_from_slice(&(file_name.len() as u16).to_le_bytes());
+    archive.extend_from_slice(file_name);
+    archive.extend_from_slice(data);
+
+    bfstool::converter::register_converter("tm2", AppendConverter);
+
+    let mut archive = bfstool::read_archive(Cursor::new(archive), bfstool::Format::Bzf2002, false)?;
+    let output = std::env::temp_dir().join("bfstool_convert_test");
+    let file_names = archive.file_names();
+    archive.extract_files_with_options(
+        file_names.clone(),
+        &output,
+        bfstool::archive_reader::ExtractOptions {
+            convert: true,
+            ..Default::default()
+        },
+        Box::new(|_, _| {}),
+    )?;
+    assert_eq!(std::fs::read(output.join("menu.tm2"))?, b"TIM2converted");
+
+    archive.extract_files(file_names, &output, Box::new(|_, _| {}))?;
+    assert_eq!(std::fs::read(output.join("menu.tm2"))?, b"TIM2");
+
+    bfstool::converter::unregister_converter("tm2");
+    std::fs::remove_dir_all(&output)?;
+
+    Ok(())
+}
+
+#[test]
+fn test_folders() -> Result<(), Box<dyn Error>> {
+    for (archive, format) in [
+        ("test_data/bfs2004a/europe.bin", bfstool::Format::Bfs2004a),
+        ("test_data/bfs2004b/fo2a.bin", bfstool::Format::Bfs2004b),
+        ("test_data/bfs2007/fouc_data.bin", bfstool::Format::Bfs2007),
+    ] {
+        let archive = bfstool::read_archive_file(&PathBuf::from(archive), format, false)?;
+        let folders = archive.folders();
+
+        let mut files = folders
+            .iter()
+            .flat_map(|folder| {
+                let files = archive.files_in_folder(folder);
+                assert!(!files.is_empty());
+                for file in &files {
+                    assert_eq!(bfstool::archive_reader::folder_name(file), folder);
+                }
+                files
+            })
+            .collect::<Vec<String>>();
+        let mut file_names = archive.file_names();
+        files.sort();
+        file_names.sort();
+        assert_eq!(files, file_names);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_path_separators() {
+    use bfstool::archive_reader::{extraction_path, folder_name, normalize_separators};
+
+    assert_eq!(
+        normalize_separators("data\\language\\version.ini"),
+        "data/language/version.ini"
+    );
+    assert_eq!(folder_name("data\\language/version.ini"), "data\\language");
+
+    let output = PathBuf::from("extracted");
+    let expected = output.join("data").join("language").join("version.ini");
+    assert_eq!(
+        extraction_path(&output, "data/language/version.ini"),
+        expected
+    );
+    assert_eq!(
+        extraction_path(&output, "data\\language\\version.ini"),
+        expected
+    );
+    assert_eq!(
+        extraction_path(&output, "/data//language/./version.ini"),
+        expected
+    );
+    assert_eq!(
+        extraction_path(&output, "..\\..\\data/language/version.ini"),
+        expected
+    );
+}
@@ -0,0 +1,48 @@
+#[test]
+#[cfg(feature = "cli")]
+fn test_strict_list() -> Result<(), Box<dyn std::error::Error>> {
+    use std::process::Command;
+
+    let data = b"1.0";
+    let file_name = b"version.ini";
+    let data_offset = 0x10 + 0x13 + file_name.len() as u32;
+
+    // Made up Bzf2002 archive storing a single uncompressed file with the unknown flag 0x02 set
+    let mut archive = Vec::new();
+    archive.extend_from_slice(b"bzf2");
+    archive.extend_from_slice(&0x20021011u32.to_le_bytes());
+    archive.extend_from_slice(&data_offset.to_le_bytes());
+    archive.extend_from_slice(&1u32.to_le_bytes());
+    archive.push(0x02);
+    archive.extend_from_slice(&data_offset.to_le_bytes());
+    archive.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    archive.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    archive.extend_from_slice(&0u32.to_le_bytes());
+    archive.extend_from_slice(&(file_name.len() as u16).to_le_bytes());
+    archive.extend_from_slice(file_name);
+    archive.extend_from_slice(data);
+    let archive_path =
+        std::env::temp_dir().join(format!("bfstool_strict_test_{}.bzf", std::process::id()));
+    std::fs::write(&archive_path, &archive)?;
+
+    let list = |strict: bool| {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_bfstool-cli"));
+        if strict {
+            command.arg("--strict");
+        }
+        command
+            .args(["list", "--format", "bzf2002"])
+            .arg(&archive_path)
+            .output()
+    };
+    let lenient = list(false);
+    let strict = list(true);
+    std::fs::remove_file(&archive_path)?;
+
+    assert!(lenient?.status.success());
+    let strict = strict?;
+    assert!(!strict.status.success());
+    assert!(String::from_utf8_lossy(&strict.stderr).contains("strict mode"));
+
+    Ok(())
+}